@@ -0,0 +1,104 @@
+//! 轴对齐包围盒的定义以及相关方法。
+
+use crate::interval::Interval;
+use crate::ray::{Point3, Ray};
+use crate::vec3::Vec3;
+
+/// 轴对齐包围盒（AABB），由两个对角顶点表示。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    /// 各个坐标分量的最小值
+    pub min: Point3,
+    /// 各个坐标分量的最大值
+    pub max: Point3,
+}
+
+impl Aabb {
+    /// 由任意两个对角顶点创建包围盒。
+    pub fn new(a: Point3, b: Point3) -> Self {
+        Self {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// 只包含一个点的包围盒。
+    pub fn point(p: Point3) -> Self {
+        Self { min: p, max: p }
+    }
+
+    /// 同时包含两个包围盒的最小包围盒。
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    /// 包围盒的中心
+    #[inline]
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.min + self.max)
+    }
+
+    /// 从最小顶点指向最大顶点的对角线向量
+    #[inline]
+    pub fn diagonal(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// 包围盒的表面积
+    pub fn surface_area(&self) -> f64 {
+        let d = self.diagonal();
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// 点是否在包围盒内（包含边界）。
+    pub fn contains(&self, p: Point3) -> bool {
+        (0..3).all(|axis| {
+            Interval::new(axis_of(self.min, axis), axis_of(self.max, axis))
+                .contains(axis_of(p, axis))
+        })
+    }
+
+    /// 光线在给定的时间范围内能否与包围盒相交。
+    pub fn hit(&self, r: Ray, interval: Interval) -> bool {
+        let (mut t_min, mut t_max) = (interval.min, interval.max);
+        for axis in 0..3 {
+            let inv_d = 1.0 / axis_of(r.direction, axis);
+            let origin = axis_of(r.origin, axis);
+            let t0 = (axis_of(self.min, axis) - origin) * inv_d;
+            let t1 = (axis_of(self.max, axis) - origin) * inv_d;
+            let (t0, t1) = if inv_d < 0.0 { (t1, t0) } else { (t0, t1) };
+            // 光线与某个坐标轴平行且起点在两个平面之间时，t0 和 t1 为 NaN，不缩小范围
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max < t_min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 取向量在第 `axis` 个坐标轴上的分量，0、1、2 分别对应 x、y、z。
+#[inline]
+pub fn axis_of(v: Vec3, axis: usize) -> f64 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
@@ -0,0 +1,101 @@
+//! 透明度遮罩，用于在任意物体上挖出镂空的部分（例如树叶、栅栏和贴花）。
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::image::Image;
+use crate::interval::Interval;
+use crate::ray::{Point3, Ray};
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+
+/// 带有透明度遮罩的物体
+///
+/// 光线击中物体时，按照碰撞点表面坐标 (u, v) 处的不透明度决定是否忽略这次碰撞，
+/// 被忽略时继续寻找同一条光线与物体更远处的交点。
+/// 不透明度介于 0 和 1 之间时随机决定，平均下来表面按比例部分透明。
+/// 作为光源时，表面采样按照采样点的不透明度随机拒绝，被拒绝时返回 None，因此透明的部分不会发光；
+/// 朝向物体的方向采样仍然覆盖整个物体，落在透明部分的方向会命中更远处的表面或者不产生贡献。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::alpha_mask::AlphaMasked;
+/// use ray_tracing_in_one_weekend::material::Lambertian;
+/// use ray_tracing_in_one_weekend::sphere::Sphere;
+/// use ray_tracing_in_one_weekend::{Color, Point3};
+///
+/// // 只保留条纹的球体
+/// let sphere = Sphere::new(Point3::zero(), 1.0, Rc::new(Lambertian::new(Color::one())));
+/// let stripes = AlphaMasked::new(sphere, |u, _| if (u * 20.0).fract() < 0.5 { 1.0 } else { 0.0 });
+/// ```
+pub struct AlphaMasked<H: Hittable> {
+    /// 被遮罩的物体
+    pub object: H,
+    /// 由表面坐标计算不透明度，0 表示完全透明，1 表示完全不透明
+    pub mask: Box<dyn Fn(f64, f64) -> f64>,
+}
+
+impl<H: Hittable> AlphaMasked<H> {
+    /// 创建一个新的带遮罩物体实例。
+    pub fn new(object: H, mask: impl Fn(f64, f64) -> f64 + 'static) -> Self {
+        Self {
+            object,
+            mask: Box::new(mask),
+        }
+    }
+
+    /// 使用灰度图像作为遮罩，颜色分量的平均值即不透明度。
+    pub fn from_image(object: H, image: Image) -> Self {
+        Self::new(object, move |u, v| {
+            let c = image.sample(u, v);
+            (c.x + c.y + c.z) / 3.0
+        })
+    }
+
+    /// 碰撞点是否被遮罩保留，不透明度为小数时随机决定。
+    fn keeps(&self, rec: &HitRecord<'_>) -> bool {
+        let alpha = (self.mask)(rec.u, rec.v);
+        if alpha >= 1.0 {
+            true
+        } else if alpha <= 0.0 {
+            false
+        } else {
+            random_double() < alpha
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for AlphaMasked<H> {
+    fn hit(&self, r: Ray, interval: Interval) -> Option<HitRecord<'_>> {
+        let mut min = interval.min;
+        loop {
+            let rec = self.object.hit(r, Interval::new(min, interval.max))?;
+            if self.keeps(&rec) {
+                return Some(rec);
+            }
+            // 忽略这次碰撞，从碰撞点之后继续寻找
+            min = rec.t;
+        }
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        self.object.random(origin)
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord<'_>> {
+        // 采样概率密度仍然是整个表面积的倒数，拒绝透明的点相当于把发光乘以不透明度
+        self.object.sample_surface().filter(|rec| self.keeps(rec))
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+}
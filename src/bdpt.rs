@@ -0,0 +1,737 @@
+//! 双向路径追踪积分器。
+//!
+//! 分别从相机和光源出发生成两条子路径，再把两条子路径上的所有顶点两两连接，
+//! 得到不同长度组合的完整路径，并使用多重重要性采样对这些采样策略加权。
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::integrator::{Integrator, MisHeuristic};
+use crate::light::{Light, LightList};
+use crate::medium::MediumStack;
+use crate::ray::{Point3, Ray};
+use crate::vec3::Vec3;
+
+/// 子路径顶点的类型
+#[allow(clippy::large_enum_variant)]
+enum VertexKind<'a> {
+    /// 相机镜头上的顶点
+    Camera,
+    /// 光源上的顶点
+    Light(&'a dyn Light),
+    /// 物体表面上的顶点，保存了碰撞记录以及到达该顶点的射线
+    Surface { rec: HitRecord<'a>, r_in: Ray },
+}
+
+/// 子路径上的一个顶点
+struct Vertex<'a> {
+    kind: VertexKind<'a>,
+    /// 顶点位置
+    p: Point3,
+    /// 顶点的几何法线，相机和点光源没有法线
+    normal: Option<Vec3>,
+    /// 从子路径起点到该顶点的吞吐量
+    beta: Color,
+    /// 沿子路径生成方向采样到该顶点的面积概率密度
+    pdf_fwd: f64,
+    /// 沿相反方向采样到该顶点的面积概率密度
+    pdf_rev: f64,
+    /// 该顶点是否发生了镜面散射
+    delta: bool,
+    /// 子路径到达该顶点时所在的介质，相机和光源假定位于所有物体之外
+    media: MediumStack<'a>,
+}
+
+impl<'a> Vertex<'a> {
+    fn camera(p: Point3) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            p,
+            normal: None,
+            beta: Color::one(),
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+            delta: false,
+            media: MediumStack::new(),
+        }
+    }
+
+    fn light(light: &'a dyn Light, p: Point3, normal: Option<Vec3>, beta: Color, pdf: f64) -> Self {
+        Self {
+            kind: VertexKind::Light(light),
+            p,
+            normal,
+            beta,
+            pdf_fwd: pdf,
+            pdf_rev: 0.0,
+            delta: false,
+            media: MediumStack::new(),
+        }
+    }
+
+    /// 顶点所在的光源是否为 delta 分布
+    fn is_delta_light(&self) -> bool {
+        matches!(self.kind, VertexKind::Light(light) if light.is_delta())
+    }
+
+    /// 顶点能否与另一条子路径的顶点相连，镜面散射的表面无法连接
+    fn is_connectible(&self) -> bool {
+        match &self.kind {
+            VertexKind::Camera | VertexKind::Light(_) => true,
+            VertexKind::Surface { .. } => !self.delta,
+        }
+    }
+
+    /// 沿 `direction` 离开该顶点时所在的介质，方向穿过表面时会进入或离开该表面的介质
+    fn outgoing_media(&self, direction: Vec3) -> MediumStack<'a> {
+        let mut media = self.media.clone();
+        if let VertexKind::Surface { rec, .. } = &self.kind {
+            media.update(rec, Ray::new(self.p, direction));
+        }
+        media
+    }
+
+    /// 从该顶点到 `target` 的连接边上的透射率，边被遮挡时返回 None
+    fn transmittance_to(&self, world: &'a dyn Hittable, target: Point3) -> Option<Color> {
+        let w = target - self.p;
+        let distance = w.length();
+        self.outgoing_media(w)
+            .shadow_transmittance(world, Ray::new(self.p, w / distance), distance)
+    }
+
+    /// 计算该顶点朝向 `next` 的 `BSDF * cosθ`，只对表面顶点有意义
+    fn eval(&self, next: Point3) -> Color {
+        match &self.kind {
+            VertexKind::Surface { rec, r_in } => {
+                rec.mat.eval(*r_in, rec, Ray::new(self.p, next - self.p))
+            }
+            _ => Color::zero(),
+        }
+    }
+
+    /// 表面顶点自身朝入射方向发出的光
+    fn emitted(&self) -> Color {
+        match &self.kind {
+            VertexKind::Surface { rec, r_in } => rec.mat.emitted(*r_in, rec),
+            _ => Color::zero(),
+        }
+    }
+
+    /// 把从该顶点出发的立体角概率密度换算为 `next` 处的面积概率密度
+    fn convert_density(&self, pdf: f64, next: Point3, next_normal: Option<Vec3>) -> f64 {
+        let w = next - self.p;
+        let distance_squared = w.length_squared();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+
+        let cos_theta = next_normal.map_or(1.0, |n| n.dot(w.unit_vector()).abs());
+        pdf * cos_theta / distance_squared
+    }
+
+    /// 从 `prev` 到达该顶点后，采样到 `next` 的面积概率密度
+    fn pdf(&self, camera: Option<&Camera>, prev: Option<&Vertex<'_>>, next: &Vertex<'_>) -> f64 {
+        let direction = next.p - self.p;
+        let pdf = match &self.kind {
+            VertexKind::Camera => camera.map_or(0.0, |c| c.importance_pdf(self.p, direction)),
+            VertexKind::Light(light) => light.pdf_emission(self.p, direction).1,
+            VertexKind::Surface { rec, .. } => match prev {
+                Some(prev) => rec.mat.scattering_pdf(
+                    Ray::new(prev.p, self.p - prev.p),
+                    rec,
+                    Ray::new(self.p, direction),
+                ),
+                None => 0.0,
+            },
+        };
+        self.convert_density(pdf, next.p, next.normal)
+    }
+
+    /// 把该表面顶点当作光源时，发射光线到达 `next` 的面积概率密度
+    fn pdf_light(&self, lights: &LightList, next: &Vertex<'_>) -> f64 {
+        let pdf_dir = lights.pdf_emission(self.p, next.p - self.p).1;
+        self.convert_density(pdf_dir, next.p, next.normal)
+    }
+
+    /// 把该表面顶点当作光源时，在光源上采样到该顶点的面积概率密度
+    fn pdf_light_origin(&self, lights: &LightList, next: &Vertex<'_>) -> f64 {
+        lights.pdf_emission(self.p, next.p - self.p).0
+    }
+}
+
+/// 连接两条子路径得到的完整路径
+struct Connection<'a> {
+    /// 未加权的路径贡献
+    contribution: Color,
+    /// 连接时新采样的顶点（s = 1 时的光源顶点或 t = 1 时的相机顶点）
+    sampled: Option<Vertex<'a>>,
+    /// t = 1 时贡献所在的像素
+    pixel: Option<(i32, i32)>,
+    /// 已经确定的 MIS 权重，为 None 时在所有采样策略之间计算
+    weight: Option<f64>,
+}
+
+impl<'a> Connection<'a> {
+    fn new(contribution: Color) -> Self {
+        Self {
+            contribution,
+            sampled: None,
+            pixel: None,
+            weight: None,
+        }
+    }
+}
+
+/// 计算 MIS 权重时使用的顶点概率密度信息
+#[derive(Copy, Clone)]
+struct VertexPdf {
+    pdf_fwd: f64,
+    pdf_rev: f64,
+    delta: bool,
+}
+
+impl From<&Vertex<'_>> for VertexPdf {
+    fn from(vertex: &Vertex<'_>) -> Self {
+        Self {
+            pdf_fwd: vertex.pdf_fwd,
+            pdf_rev: vertex.pdf_rev,
+            delta: vertex.delta,
+        }
+    }
+}
+
+/// 双向路径追踪积分器
+///
+/// 适合焦散、光线通过小开口照亮房间等单向路径追踪难以处理的场景。
+/// 通过相机渲染时，从光源出发的子路径还会直接连接到相机上，贡献到对应的像素。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::bdpt::Bdpt;
+/// use ray_tracing_in_one_weekend::camera::CameraBuilder;
+/// use ray_tracing_in_one_weekend::color::luminance;
+/// use ray_tracing_in_one_weekend::film::Film;
+/// use ray_tracing_in_one_weekend::hittable_list::HittableList;
+/// use ray_tracing_in_one_weekend::integrator::{Integrator, PathTracer};
+/// use ray_tracing_in_one_weekend::light::{AreaLight, LightList};
+/// use ray_tracing_in_one_weekend::material::{DiffuseLight, Lambertian};
+/// use ray_tracing_in_one_weekend::sphere::Sphere;
+/// use ray_tracing_in_one_weekend::utils::random_double;
+/// use ray_tracing_in_one_weekend::{Color, Point3};
+///
+/// // 封闭的漫反射房间，里面有一个球形的灯
+/// let room = Rc::new(Lambertian::new(Color::new(0.3, 0.3, 0.3)));
+/// let lamp = Rc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+/// let bulb = || Sphere::new(Point3::new(0.0, 1.0, 0.0), 0.8, lamp.clone());
+/// let mut world = HittableList::new();
+/// world.add(Box::new(Sphere::new(Point3::zero(), 2.0, room)));
+/// world.add(Box::new(bulb()));
+/// let mut lights = LightList::new();
+/// lights.add(Box::new(AreaLight::new(Box::new(bulb()))));
+///
+/// // 房间里看向地面的 4×4 小图，光源子路径连接到相机的贡献累加在胶片上
+/// let camera = CameraBuilder::default()
+///     .aspect_ratio(1.0)
+///     .image_width(4)
+///     .vfov(60.0)
+///     .look_from(Point3::new(0.0, -0.5, 0.0))
+///     .look_at(Point3::new(0.0, -2.0, 0.5))
+///     .build();
+/// let spp = 100;
+/// let mean_luminance = |integrator: &dyn Integrator| {
+///     let mut film = Film::new(4, 4);
+///     for _ in 0..spp {
+///         for y in 0..4 {
+///             for x in 0..4 {
+///                 let fx = x as f64 + random_double();
+///                 let fy = y as f64 + random_double();
+///                 let ray = camera.ray_at(fx, fy).unwrap();
+///                 let color =
+///                     integrator.camera_ray_color(ray, &camera, &mut film, &world, &lights);
+///                 film.add(x, y, color);
+///             }
+///         }
+///     }
+///     let sum: f64 = (0..16).map(|i| luminance(film.get(i % 4, i / 4))).sum();
+///     sum / (16 * spp) as f64
+/// };
+///
+/// // 两种积分器收敛到同一张图像
+/// let reference = mean_luminance(&PathTracer::new(10));
+/// let bdpt = mean_luminance(&Bdpt::new(10));
+/// assert!((bdpt - reference).abs() < 0.05 * reference, "{bdpt} {reference}");
+/// ```
+pub struct Bdpt {
+    /// 完整路径的最大散射次数
+    pub max_depth: i32,
+    /// 对不同采样策略加权时使用的启发式
+    pub mis_heuristic: MisHeuristic,
+}
+
+impl Bdpt {
+    /// 创建一个新的双向路径追踪积分器实例。
+    pub fn new(max_depth: i32) -> Self {
+        Self {
+            max_depth,
+            mis_heuristic: MisHeuristic::default(),
+        }
+    }
+
+    /// 从 `ray` 开始随机游走，把经过的表面顶点追加到 `path` 中，直到顶点数达到 `max_vertices`。
+    ///
+    /// # 参数
+    ///
+    /// * `pdf` - 采样得到 `ray` 方向的立体角概率密度。
+    /// * `escaped` - 不为 None 时，累加路径逃逸到背景时带回的颜色，背景由其中的光源列表给出。
+    #[allow(clippy::too_many_arguments)]
+    fn random_walk<'a>(
+        &self,
+        world: &'a dyn Hittable,
+        mut ray: Ray,
+        mut beta: Color,
+        pdf: f64,
+        max_vertices: usize,
+        path: &mut Vec<Vertex<'a>>,
+        mut escaped: Option<(&mut Color, &LightList)>,
+    ) {
+        let mut pdf_fwd = pdf;
+        // 子路径当前所在的介质，两端的起点都假定位于所有物体之外
+        let mut media = MediumStack::new();
+        while path.len() < max_vertices {
+            let (hit, transmittance) = media.hit(world, ray);
+            beta = beta * transmittance;
+            let Some(rec) = hit else {
+                if let Some((escaped, lights)) = escaped.as_mut() {
+                    // 从非镜面表面逃逸时，背景中的太阳等也可能由光源采样得到，按照 MIS 权重计入
+                    let mut radiance = lights.background(ray);
+                    if path.len() > 1 && pdf_fwd > 0.0 {
+                        let light_pdf = lights.choose_pdf(ray.origin, ray.direction);
+                        radiance *= self.mis_heuristic.weight(pdf_fwd, light_pdf);
+                    }
+                    **escaped += beta * radiance;
+                }
+                break;
+            };
+
+            let p = rec.p;
+            let normal = rec.normal;
+            let prev = path.last().expect("sub-path must start with an end point");
+            let pdf_area = prev.convert_density(pdf_fwd, p, Some(normal));
+            let srec = rec.mat.scatter(ray, &rec);
+            let arrived = media.clone();
+            if let Some(srec) = &srec {
+                media.update(&rec, srec.scattered);
+            }
+
+            // 反向采样的概率密度：从新的散射方向到达该顶点后，沿原入射方向返回
+            let pdf_rev = srec.as_ref().map_or(0.0, |srec| {
+                if srec.is_specular {
+                    0.0
+                } else {
+                    rec.mat.scattering_pdf(
+                        Ray::new(p + srec.scattered.direction, -srec.scattered.direction),
+                        &rec,
+                        Ray::new(p, -ray.direction),
+                    )
+                }
+            });
+
+            path.push(Vertex {
+                kind: VertexKind::Surface { rec, r_in: ray },
+                p,
+                normal: Some(normal),
+                beta,
+                pdf_fwd: pdf_area,
+                pdf_rev: 0.0,
+                delta: false,
+                media: arrived,
+            });
+            if path.len() >= max_vertices {
+                break;
+            }
+            let Some(srec) = srec else {
+                break;
+            };
+
+            let n = path.len();
+            let (prev_p, prev_normal) = (path[n - 2].p, path[n - 2].normal);
+            path[n - 2].pdf_rev = path[n - 1].convert_density(pdf_rev, prev_p, prev_normal);
+            path[n - 1].delta = srec.is_specular;
+            pdf_fwd = if srec.is_specular { 0.0 } else { srec.pdf };
+
+            beta = beta * srec.attenuation;
+            if beta.near_zero() {
+                break;
+            }
+            ray = srec.scattered;
+        }
+    }
+
+    /// 生成从光源出发的子路径
+    fn light_subpath<'a>(&self, world: &'a dyn Hittable, lights: &'a LightList) -> Vec<Vertex<'a>> {
+        let mut path = Vec::new();
+        let Some((light, pmf)) = lights.choose() else {
+            return path;
+        };
+        // 无限远光源的光照已经由逃逸的相机子路径和光源采样之间的 MIS 完整计入
+        if light.is_infinite() {
+            return path;
+        }
+        let Some(emission) = light.sample_emission() else {
+            return path;
+        };
+        if emission.pdf_pos <= 0.0 || emission.pdf_dir <= 0.0 || emission.radiance.near_zero() {
+            return path;
+        }
+
+        path.push(Vertex::light(
+            light,
+            emission.ray.origin,
+            emission.normal,
+            emission.radiance,
+            emission.pdf_pos * pmf,
+        ));
+
+        let cos_theta = emission
+            .normal
+            .map_or(1.0, |n| n.dot(emission.ray.direction).abs());
+        let beta = emission.radiance * cos_theta / (pmf * emission.pdf_pos * emission.pdf_dir);
+        self.random_walk(
+            world,
+            emission.ray,
+            beta,
+            emission.pdf_dir,
+            self.max_depth as usize + 1,
+            &mut path,
+            None,
+        );
+        path
+    }
+
+    /// 连接相机子路径的前 `t` 个顶点和光源子路径的前 `s` 个顶点，计算完整路径未加权的贡献。
+    ///
+    /// 连接边上的介质由边的起点决定，边的贡献会乘上沿途的透射率。
+    /// 两条子路径无法连接时返回 None。
+    #[allow(clippy::too_many_arguments)]
+    fn connect<'a>(
+        &self,
+        world: &'a dyn Hittable,
+        lights: &'a LightList,
+        camera: Option<&Camera>,
+        camera_path: &[Vertex<'a>],
+        light_path: &[Vertex<'a>],
+        s: usize,
+        t: usize,
+    ) -> Option<Connection<'a>> {
+        if s == 0 {
+            // 相机子路径自己命中了光源
+            let pt = &camera_path[t - 1];
+            return Some(Connection::new(pt.beta * pt.emitted()));
+        }
+
+        if t == 1 {
+            // 光源子路径上的顶点直接连接到相机
+            let qs = &light_path[s - 1];
+            if !qs.is_connectible() {
+                return None;
+            }
+            let sample = camera?.sample_importance(qs.p)?;
+            let transmittance = qs.transmittance_to(world, sample.lens_point)?;
+
+            let distance_squared = (sample.lens_point - qs.p).length_squared();
+            let color = qs.beta * qs.eval(sample.lens_point) * transmittance * sample.importance
+                / distance_squared;
+            let mut sampled = Vertex::camera(sample.lens_point);
+            sampled.beta = Color::one() * sample.importance;
+            return Some(Connection {
+                contribution: color,
+                sampled: Some(sampled),
+                pixel: Some((sample.x, sample.y)),
+                weight: None,
+            });
+        }
+
+        let pt = &camera_path[t - 1];
+        if !pt.is_connectible() {
+            return None;
+        }
+
+        if s == 1 {
+            // 在光源上采样一个点，与相机子路径的顶点相连
+            let (light, pmf) = lights.choose()?;
+            let sample = light.sample(pt.p)?;
+            if sample.pdf <= 0.0 || sample.radiance.near_zero() {
+                return None;
+            }
+
+            if light.is_infinite() {
+                // 天空等无限远光源不生成光源子路径，只在光源采样与逃逸到背景的相机子路径之间做 MIS
+                let shadow_ray = Ray::new(pt.p, sample.direction);
+                let transmittance = pt.outgoing_media(sample.direction).shadow_transmittance(
+                    world,
+                    shadow_ray,
+                    sample.distance,
+                )?;
+                let bsdf_pdf = match &pt.kind {
+                    VertexKind::Surface { rec, r_in } => {
+                        rec.mat.scattering_pdf(*r_in, rec, shadow_ray)
+                    }
+                    _ => 0.0,
+                };
+                let light_pdf = sample.pdf * pmf;
+                let color =
+                    pt.beta * pt.eval(pt.p + sample.direction) * transmittance * sample.radiance
+                        / light_pdf;
+                return Some(Connection {
+                    contribution: color,
+                    sampled: None,
+                    pixel: None,
+                    weight: Some(self.mis_heuristic.weight(light_pdf, bsdf_pdf)),
+                });
+            }
+
+            let light_point = pt.p + sample.distance * sample.direction;
+            let transmittance = pt.transmittance_to(world, light_point)?;
+
+            let pdf_pos = if light.is_delta() {
+                1.0
+            } else {
+                light.pdf_emission(light_point, -sample.direction).0
+            };
+            let sampled = Vertex::light(
+                light,
+                light_point,
+                sample.normal,
+                sample.radiance / (sample.pdf * pmf),
+                pdf_pos * pmf,
+            );
+            let color = pt.beta * pt.eval(light_point) * transmittance * sampled.beta;
+            return Some(Connection {
+                contribution: color,
+                sampled: Some(sampled),
+                pixel: None,
+                weight: None,
+            });
+        }
+
+        // 一般情况：连接两条子路径上的表面顶点
+        let qs = &light_path[s - 1];
+        if !qs.is_connectible() {
+            return None;
+        }
+        let distance_squared = (qs.p - pt.p).length_squared();
+        let color = qs.beta * qs.eval(pt.p) * pt.eval(qs.p) * pt.beta / distance_squared;
+        if color.near_zero() {
+            return None;
+        }
+        let transmittance = pt.transmittance_to(world, qs.p)?;
+        Some(Connection::new(color * transmittance))
+    }
+
+    /// 计算由 `s` 个光源子路径顶点和 `t` 个相机子路径顶点组成的完整路径的 MIS 权重。
+    ///
+    /// 通过依次计算其他采样策略与当前策略的概率密度之比，得到当前策略的权重。
+    #[allow(clippy::too_many_arguments)]
+    fn mis_weight(
+        &self,
+        lights: &LightList,
+        camera: Option<&Camera>,
+        camera_path: &[Vertex<'_>],
+        light_path: &[Vertex<'_>],
+        sampled: Option<&Vertex<'_>>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        // 连接时新采样的顶点替换掉子路径上对应位置的顶点
+        let qs = match (s, sampled) {
+            (0, _) => None,
+            (1, Some(sampled)) => Some(sampled),
+            _ => Some(&light_path[s - 1]),
+        };
+        let pt = match (t, sampled) {
+            (1, Some(sampled)) => sampled,
+            _ => &camera_path[t - 1],
+        };
+        let qs_minus = if s > 1 {
+            Some(&light_path[s - 2])
+        } else {
+            None
+        };
+        let pt_minus = if t > 1 {
+            Some(&camera_path[t - 2])
+        } else {
+            None
+        };
+
+        let mut camera_pdfs: Vec<VertexPdf> = camera_path[..t - 1]
+            .iter()
+            .chain(std::iter::once(pt))
+            .map(VertexPdf::from)
+            .collect();
+        let mut light_pdfs: Vec<VertexPdf> = light_path[..s.saturating_sub(1)]
+            .iter()
+            .chain(qs)
+            .map(VertexPdf::from)
+            .collect();
+
+        // 连接处的两个顶点不再是镜面散射
+        camera_pdfs[t - 1].delta = false;
+        if let Some(qs) = qs {
+            light_pdfs[s - 1].delta = false;
+
+            // 从光源子路径一侧到达连接处相机顶点及其前一个顶点的概率密度
+            camera_pdfs[t - 1].pdf_rev = qs.pdf(camera, qs_minus, pt);
+            if let Some(pt_minus) = pt_minus {
+                camera_pdfs[t - 2].pdf_rev = pt.pdf(camera, Some(qs), pt_minus);
+            }
+
+            // 从相机子路径一侧到达连接处光源顶点及其前一个顶点的概率密度
+            light_pdfs[s - 1].pdf_rev = pt.pdf(camera, pt_minus, qs);
+            if let Some(qs_minus) = qs_minus {
+                light_pdfs[s - 2].pdf_rev = qs.pdf(camera, Some(pt), qs_minus);
+            }
+        } else if let Some(pt_minus) = pt_minus {
+            // 相机子路径直接命中光源，连接处的顶点被当作光源处理
+            let pdf_origin = pt.pdf_light_origin(lights, pt_minus);
+            if pdf_origin <= 0.0 {
+                // 光源不在可采样的光源列表中，只有这一种策略能得到该路径
+                return 1.0;
+            }
+            camera_pdfs[t - 1].pdf_rev = pdf_origin;
+            camera_pdfs[t - 2].pdf_rev = pt.pdf_light(lights, pt_minus);
+        }
+
+        let remap = |pdf: f64| if pdf != 0.0 { pdf } else { 1.0 };
+        let power = |ratio: f64| match self.mis_heuristic {
+            MisHeuristic::Balance => ratio,
+            MisHeuristic::Power => ratio * ratio,
+        };
+
+        // 依次考虑相机子路径更短、光源子路径更长的策略
+        let mut sum = 0.0;
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(camera_pdfs[i].pdf_rev) / remap(camera_pdfs[i].pdf_fwd);
+            // 没有相机时无法把光源子路径直接连接到相机上
+            let available = i > 1 || camera.is_some();
+            if available && !camera_pdfs[i].delta && !camera_pdfs[i - 1].delta {
+                sum += power(ratio);
+            }
+        }
+
+        // 依次考虑光源子路径更短、相机子路径更长的策略
+        let mut ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light_pdfs[i].pdf_rev) / remap(light_pdfs[i].pdf_fwd);
+            let delta_light = if i > 0 {
+                light_pdfs[i - 1].delta
+            } else {
+                qs.filter(|_| s == 1)
+                    .unwrap_or_else(|| &light_path[0])
+                    .is_delta_light()
+            };
+            if !light_pdfs[i].delta && !delta_light {
+                sum += power(ratio);
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+
+    /// 双向路径追踪的主体逻辑，`camera` 和 `film` 为 None 时不考虑光源子路径直接连接到相机的策略。
+    fn trace(
+        &self,
+        r: Ray,
+        camera: Option<&Camera>,
+        mut film: Option<&mut Film>,
+        world: &dyn Hittable,
+        lights: &LightList,
+    ) -> Color {
+        // 生成相机子路径，逃逸到背景的颜色只能由这条子路径得到，直接计入
+        let mut color = Color::zero();
+        let mut camera_path = vec![Vertex::camera(r.origin)];
+        let pdf_dir = camera.map_or(1.0, |c| c.importance_pdf(r.origin, r.direction));
+        self.random_walk(
+            world,
+            r,
+            Color::one(),
+            pdf_dir,
+            self.max_depth as usize + 2,
+            &mut camera_path,
+            Some((&mut color, lights)),
+        );
+
+        let light_path = self.light_subpath(world, lights);
+
+        for t in 1..=camera_path.len() {
+            // s = 1 时在光源上重新采样，即使光源子路径为空（例如选中了天空）也要考虑
+            for s in 0..=light_path.len().max(1) {
+                let depth = t as i32 + s as i32 - 2;
+                if (s == 1 && t == 1) || depth < 0 || depth > self.max_depth {
+                    continue;
+                }
+                if t == 1 && camera.is_none() {
+                    continue;
+                }
+
+                let Some(Connection {
+                    contribution,
+                    sampled,
+                    pixel,
+                    weight,
+                }) = self.connect(world, lights, camera, &camera_path, &light_path, s, t)
+                else {
+                    continue;
+                };
+                if contribution.near_zero() {
+                    continue;
+                }
+
+                let weight = weight.unwrap_or_else(|| {
+                    self.mis_weight(
+                        lights,
+                        camera,
+                        &camera_path,
+                        &light_path,
+                        sampled.as_ref(),
+                        s,
+                        t,
+                    )
+                });
+                match (pixel, film.as_deref_mut()) {
+                    (Some((x, y)), Some(film)) => film.add(x, y, weight * contribution),
+                    _ => color += weight * contribution,
+                }
+            }
+        }
+
+        color
+    }
+}
+
+impl Integrator for Bdpt {
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color {
+        self.trace(r, None, None, world, lights)
+    }
+
+    fn camera_ray_color(
+        &self,
+        r: Ray,
+        camera: &Camera,
+        film: &mut Film,
+        world: &dyn Hittable,
+        lights: &LightList,
+    ) -> Color {
+        if !camera.supports_importance() {
+            // 正交投影等相机无法连接光源子路径，退化为不使用胶片的情形
+            return self.trace(r, None, None, world, lights);
+        }
+        self.trace(r, Some(camera), Some(film), world, lights)
+    }
+}
@@ -1,11 +1,61 @@
 //! 相机和Builder的定义以及相关工具方法。
 
-use crate::color::{Color, write_color};
+use crate::color::write_color;
+use crate::film::Film;
 use crate::hittable::Hittable;
-use crate::interval::Interval;
+use crate::integrator::{Integrator, MisHeuristic, PathTracer};
+use crate::light::LightList;
 use crate::ray::{Point3, Ray};
 use crate::utils::random_double_range_inclusive;
 use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+/// 相机的投影方式
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum Projection {
+    /// 透视投影，视野由 `vfov` 决定
+    #[default]
+    Perspective,
+    /// 正交投影，所有射线都平行于相机朝向，适合技术图纸和立面图
+    ///
+    /// 视窗宽度由 `view_height` 和图像宽高比决定，不支持散焦模糊。
+    Orthographic {
+        /// 视窗在世界空间中的高度
+        view_height: f64,
+    },
+    /// 鱼眼投影，在图像中央内切圆内覆盖以相机朝向为中心、视场角为 `fov` 的圆锥，圆外为黑色
+    ///
+    /// 所有射线都从相机中心出发，不支持散焦模糊。
+    Fisheye {
+        /// 内切圆直径对应的视场角，单位为角度，最大为 360
+        fov: f64,
+        /// 射线与朝向的夹角到图像上半径的映射方式
+        mapping: FisheyeMapping,
+    },
+    /// 等距柱状投影的 360°×180° 全景图，图像中央为相机朝向，宽高比应为 2:1
+    ///
+    /// 图像从左到右对应从相机后方开始向右转一周，与常见的全景图一致；
+    /// 注意这与 [`EnvironmentLight`](crate::environment::EnvironmentLight) 沿用的球面坐标约定左右相反。
+    /// 所有射线都从相机中心出发，不支持散焦模糊。
+    Equirectangular,
+    /// 横向排列的立方体贴图六个面，宽高比应为 6:1
+    ///
+    /// 各个面依次为相机坐标系的 +X、-X、+Y、-Y、+Z、-Z 方向，其中 X 指向右方、Y 指向上方、
+    /// Z 指向相机朝向的反方向，每个面的朝向与 OpenGL 立方体贴图的约定相同。
+    /// 使用默认的朝向（-Z 方向）和上方向时，六个面与世界坐标轴对齐。
+    /// 所有射线都从相机中心出发，不支持散焦模糊。
+    CubeMap,
+}
+
+/// 鱼眼镜头把射线与相机朝向的夹角 θ 映射到图像上半径 r 的方式，r 以内切圆半径归一化
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum FisheyeMapping {
+    /// 等距投影：r 与 θ 成正比
+    #[default]
+    Equidistant,
+    /// 等立体角投影：r 与 sin(θ / 2) 成正比，图像上相同的面积对应相同的立体角
+    Equisolid,
+}
 
 /// 相机构建参数
 ///
@@ -21,11 +71,15 @@ pub struct CameraBuilder {
     samples_per_pixel: i32,
     max_depth: i32,
     vfov: f64,
+    projection: Projection,
     look_from: Point3,
     look_at: Point3,
     up: Vec3,
     defocus_angle: f64,
     focus_dist: f64,
+    mis_heuristic: MisHeuristic,
+    russian_roulette_depth: Option<i32>,
+    integrator: Option<Box<dyn Integrator>>,
 }
 
 impl Default for CameraBuilder {
@@ -36,11 +90,15 @@ impl Default for CameraBuilder {
             samples_per_pixel: 100,
             max_depth: 10,
             vfov: 90.0,
+            projection: Projection::default(),
             look_from: Point3::zero(),
             look_at: -Point3::unit_z(),
             up: Vec3::unit_y(),
             defocus_angle: 0.0,
             focus_dist: 10.0,
+            mis_heuristic: MisHeuristic::default(),
+            russian_roulette_depth: None,
+            integrator: None,
         }
     }
 }
@@ -71,6 +129,20 @@ impl CameraBuilder {
         self
     }
 
+    /// 设置相机的投影方式，默认为透视投影。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::camera::{CameraBuilder, Projection};
+    /// let camera = CameraBuilder::default()
+    ///     .projection(Projection::Orthographic { view_height: 4.0 })
+    ///     .build();
+    /// ```
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
     pub fn look_from(mut self, look_from: Point3) -> Self {
         self.look_from = look_from;
         self
@@ -86,6 +158,7 @@ impl CameraBuilder {
         self
     }
 
+    /// 设置散焦模糊的光圈角度，只对透视投影有效，其他投影方式会忽略该参数。
     pub fn defocus_angle(mut self, defocus_angle: f64) -> Self {
         self.defocus_angle = defocus_angle;
         self
@@ -96,16 +169,42 @@ impl CameraBuilder {
         self
     }
 
+    pub fn mis_heuristic(mut self, mis_heuristic: MisHeuristic) -> Self {
+        self.mis_heuristic = mis_heuristic;
+        self
+    }
+
+    /// 启用俄罗斯轮盘赌，路径在达到 `min_depth` 次散射后根据吞吐量随机终止。
+    ///
+    /// `max_depth` 仍然作为路径深度的硬性上限，启用后可以把它设置得更大。
+    pub fn russian_roulette(mut self, min_depth: i32) -> Self {
+        self.russian_roulette_depth = Some(min_depth);
+        self
+    }
+
+    /// 指定渲染时使用的积分器。
+    ///
+    /// 未指定时使用 [`PathTracer`]，并应用 `max_depth`、`mis_heuristic` 和 `russian_roulette` 参数。
+    pub fn integrator(mut self, integrator: Box<dyn Integrator>) -> Self {
+        self.integrator = Some(integrator);
+        self
+    }
+
     pub fn build(self) -> Camera {
         // 计算画布高度
         let image_height = (self.image_width as f64 / self.aspect_ratio) as i32;
         let image_height = if image_height < 1 { 1 } else { image_height };
 
         // 计算视窗宽高
-        let theta = self.vfov.to_radians();
-        let h = (theta / 2.0).tan();
-
-        let viewport_height = 2.0 * h * self.focus_dist;
+        let viewport_height = match self.projection {
+            Projection::Orthographic { view_height } => view_height,
+            // 鱼眼和全景投影不使用视窗，与透视投影一样计算
+            _ => {
+                let theta = self.vfov.to_radians();
+                let h = (theta / 2.0).tan();
+                2.0 * h * self.focus_dist
+            }
+        };
         let viewport_width = viewport_height * (self.image_width as f64 / image_height as f64);
         let center = self.look_from;
 
@@ -137,6 +236,7 @@ impl CameraBuilder {
             samples_per_pixel: self.samples_per_pixel,
             max_depth: self.max_depth,
             vfov: self.vfov,
+            projection: self.projection,
             image_height,
             center,
             pixel_00_loc,
@@ -146,6 +246,19 @@ impl CameraBuilder {
             defocus_angle: self.defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
+            u,
+            v,
+            w,
+            focus_dist: self.focus_dist,
+            viewport_up_left,
+            viewport_area: viewport_width * viewport_height,
+            integrator: self.integrator.unwrap_or_else(|| {
+                Box::new(PathTracer {
+                    max_depth: self.max_depth,
+                    mis_heuristic: self.mis_heuristic,
+                    russian_roulette_depth: self.russian_roulette_depth,
+                })
+            }),
         }
     }
 }
@@ -154,8 +267,8 @@ impl CameraBuilder {
 ///
 /// 示例：
 /// ```
-/// use crate::camera::Camera;
-/// let camera = Camera::new(16.0 / 9.0, 400, 100);
+/// use ray_tracing_in_one_weekend::camera::CameraBuilder;
+/// let camera = CameraBuilder::default().image_width(400).samples_per_pixel(100).build();
 /// ```
 #[allow(dead_code)]
 pub struct Camera {
@@ -164,6 +277,7 @@ pub struct Camera {
     samples_per_pixel: i32,
     max_depth: i32,
     vfov: f64,
+    projection: Projection,
     image_height: i32,
     center: Point3,
     pixel_00_loc: Point3,
@@ -173,67 +287,204 @@ pub struct Camera {
     defocus_angle: f64,
     defocus_disk_u: Vec3,
     defocus_disk_v: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    focus_dist: f64,
+    viewport_up_left: Point3,
+    viewport_area: f64,
+    integrator: Box<dyn Integrator>,
+}
+
+/// 从场景中的一点向相机采样的结果，用于从光源出发的路径直接连接到相机。
+pub struct CameraSample {
+    /// 镜头上的采样点
+    pub lens_point: Point3,
+    /// 该点在图像上对应像素的 x 坐标
+    pub x: i32,
+    /// 该点在图像上对应像素的 y 坐标
+    pub y: i32,
+    /// 相机在该方向上的重要性，数值上等于相机沿该方向生成射线的立体角概率密度
+    pub importance: f64,
 }
 
 impl Camera {
     const P3_MAGIC_NUMBER: &str = "P3";
     const MAX_COLOR_VALUE: i32 = 255;
 
-    /// 计算一条射线的颜色。
+    /// 生成一条射线，像素内的采样点随机抖动，透视投影时还会在镜头上采样产生散焦模糊。
     ///
     /// # 参数
     ///
-    /// * `r` - 要计算颜色的射线。
-    /// * `world` - 场景中的可命中对象。
+    /// * `x` - 像素的 x 坐标。
+    /// * `y` - 像素的 y 坐标。
     ///
     /// # 返回值
     ///
-    /// 射线的颜色。
-    fn ray_color(&self, r: Ray, world: &dyn Hittable, depth: i32) -> Color {
-        // 如果递归深度为 0，那么返回黑色
-        if depth <= 0 {
-            return Color::zero();
-        }
-
-        // 如果命中了物体，那么计算物体颜色
-        if let Some(rec) = world.hit(r, Interval::new(0.001, f64::INFINITY)) {
-            return if let Some((albedo, scattered)) = rec.mat.scatter(r, &rec) {
-                albedo * self.ray_color(scattered, world, depth - 1)
-            } else {
-                Color::zero()
-            };
+    /// 像素采样点不对应任何方向（例如在鱼眼图像的圆外）时返回 None。
+    fn get_ray(&self, x: i32, y: i32) -> Option<Ray> {
+        let offset = self.sample_square();
+        let ray = self.ray_at(x as f64 + 0.5 + offset.x, y as f64 + 0.5 + offset.y)?;
+        if self.projection != Projection::Perspective || self.defocus_angle <= 0.0 {
+            return Some(ray);
         }
 
-        // 这里实现一个从蓝色到白色的线性差值
-        let unit_direction = r.direction.unit_vector();
-        let a = 0.5 * (unit_direction.y + 1.0);
-        (1.0 - a) * Color::one() + a * Color::new(0.5, 0.7, 1.0)
+        // 从镜头上的随机点射向焦平面上的同一点
+        let focus_point = ray.origin + ray.direction;
+        let ray_origin = self.defocus_disk_sample();
+        Some(Ray::new(ray_origin, focus_point - ray_origin))
     }
 
-    /// 生成一条射线。
+    /// 图像上连续坐标 `(fx, fy)` 处对应的射线，像素 `(x, y)` 覆盖 `[x, x + 1) × [y, y + 1)` 的范围。
     ///
-    /// # 参数
-    ///
-    /// * `x` - 像素的 x 坐标。
-    /// * `y` - 像素的 y 坐标。
+    /// 透视投影时为从相机中心到焦平面上对应点的射线，正交投影时为穿过该点、平行于相机朝向的射线，
+    /// 鱼眼和全景投影时为从相机中心出发、沿该点对应方向的射线。不考虑散焦模糊。
     ///
     /// # 返回值
     ///
-    /// 从相机中心到像素采样点的射线。
-    fn get_ray(&self, x: i32, y: i32) -> Ray {
-        let offset = self.sample_square();
-        let pixel_center = self.pixel_00_loc
-            + (x as f64 + offset.x) * self.pixel_delta_u
-            + (y as f64 + offset.y) * self.pixel_delta_v;
+    /// 该点不对应任何方向（例如在鱼眼图像的圆外）时返回 None。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::camera::{CameraBuilder, FisheyeMapping, Projection};
+    /// use ray_tracing_in_one_weekend::vec3::Vec3;
+    ///
+    /// let direction_at = |projection, aspect_ratio, fx, fy| {
+    ///     let camera = CameraBuilder::default()
+    ///         .aspect_ratio(aspect_ratio)
+    ///         .image_width(600)
+    ///         .projection(projection)
+    ///         .build();
+    ///     camera.ray_at(fx, fy).map(|ray| ray.direction.unit_vector())
+    /// };
+    /// let close = |a: Vec3, b: Vec3| (a - b).length() < 1e-9;
+    /// // 默认的相机位于原点、朝向 -Z，因此 -w 即 -Z
+    /// let forward = -Vec3::unit_z();
+    ///
+    /// // 正交投影：所有射线都沿相机朝向，起点铺满 view_height × (view_height · 宽高比) 的矩形
+    /// let ortho = CameraBuilder::default()
+    ///     .aspect_ratio(2.0)
+    ///     .image_width(600)
+    ///     .projection(Projection::Orthographic { view_height: 4.0 })
+    ///     .build();
+    /// let corners = [(0.0, 0.0, -4.0, 2.0), (600.0, 300.0, 4.0, -2.0), (300.0, 150.0, 0.0, 0.0)];
+    /// for (fx, fy, x, y) in corners {
+    ///     let ray = ortho.ray_at(fx, fy).unwrap();
+    ///     assert!(close(ray.direction.unit_vector(), forward));
+    ///     assert!(close(ray.origin, Vec3::new(x, y, 0.0)));
+    /// }
+    ///
+    /// // 鱼眼：图像中心对应相机朝向，内切圆外的像素没有射线
+    /// let fisheye = Projection::Fisheye { fov: 180.0, mapping: FisheyeMapping::Equidistant };
+    /// assert!(close(direction_at(fisheye, 1.0, 300.0, 300.0).unwrap(), forward));
+    /// assert!(direction_at(fisheye, 1.0, 1.0, 1.0).is_none());
+    /// // 内切圆边缘与朝向成 90°
+    /// assert!(close(direction_at(fisheye, 1.0, 600.0, 300.0).unwrap(), Vec3::unit_x()));
+    ///
+    /// // 全景图：图像中心对应相机朝向，右边四分之一处为右方
+    /// let pano = Projection::Equirectangular;
+    /// assert!(close(direction_at(pano, 2.0, 300.0, 150.0).unwrap(), forward));
+    /// assert!(close(direction_at(pano, 2.0, 450.0, 150.0).unwrap(), Vec3::unit_x()));
+    ///
+    /// // 立方体贴图：六个面的中心依次为 +X、-X、+Y、-Y、+Z、-Z
+    /// let faces = [
+    ///     Vec3::unit_x(),
+    ///     -Vec3::unit_x(),
+    ///     Vec3::unit_y(),
+    ///     -Vec3::unit_y(),
+    ///     Vec3::unit_z(),
+    ///     -Vec3::unit_z(),
+    /// ];
+    /// for (i, axis) in faces.into_iter().enumerate() {
+    ///     let direction = direction_at(Projection::CubeMap, 6.0, 100.0 * i as f64 + 50.0, 50.0);
+    ///     assert!(close(direction.unwrap(), axis));
+    /// }
+    /// ```
+    pub fn ray_at(&self, fx: f64, fy: f64) -> Option<Ray> {
+        let pixel_center =
+            self.pixel_00_loc + (fx - 0.5) * self.pixel_delta_u + (fy - 0.5) * self.pixel_delta_v;
+
+        let direction = match self.projection {
+            Projection::Perspective => {
+                return Some(Ray::new(self.center, pixel_center - self.center));
+            }
+            Projection::Orthographic { .. } => {
+                // 射线从过相机中心、垂直于朝向的平面出发
+                return Some(Ray::new(pixel_center + self.focus_dist * self.w, -self.w));
+            }
+            Projection::Fisheye { fov, mapping } => self.fisheye_direction(fx, fy, fov, mapping)?,
+            Projection::Equirectangular => self.equirectangular_direction(fx, fy),
+            Projection::CubeMap => self.cube_map_direction(fx, fy),
+        };
+        Some(Ray::new(self.center, direction))
+    }
 
-        let ray_direction = pixel_center - self.center;
-        let ray_origin = if self.defocus_angle <= 0.0 {
-            self.center
-        } else {
-            self.defocus_disk_sample()
+    /// 把相机坐标系（X 向右、Y 向上、Z 指向朝向的反方向）下的向量转换到世界坐标系。
+    #[inline]
+    fn to_world(&self, local: Vec3) -> Vec3 {
+        local.x * self.u + local.y * self.v + local.z * self.w
+    }
+
+    /// 鱼眼投影下图像坐标 (fx, fy) 对应的射线方向，在内切圆外时返回 None。
+    fn fisheye_direction(
+        &self,
+        fx: f64,
+        fy: f64,
+        fov: f64,
+        mapping: FisheyeMapping,
+    ) -> Option<Vec3> {
+        let (width, height) = (self.image_width as f64, self.image_height as f64);
+        let radius = width.min(height) / 2.0;
+        let dx = (fx - width / 2.0) / radius;
+        let dy = (height / 2.0 - fy) / radius;
+        let r = (dx * dx + dy * dy).sqrt();
+        if r > 1.0 {
+            return None;
+        }
+
+        let theta_max = (fov.to_radians() / 2.0).min(PI);
+        let theta = match mapping {
+            FisheyeMapping::Equidistant => r * theta_max,
+            FisheyeMapping::Equisolid => 2.0 * (r * (theta_max / 2.0).sin()).asin(),
         };
+        let phi = dy.atan2(dx);
+        let sin_theta = theta.sin();
+        Some(self.to_world(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            -theta.cos(),
+        )))
+    }
+
+    /// 等距柱状投影下图像坐标 (fx, fy) 对应的射线方向。
+    fn equirectangular_direction(&self, fx: f64, fy: f64) -> Vec3 {
+        // 方位角从朝向开始向右增大，仰角从图像顶部的 90° 减小到底部的 -90°
+        let phi = (fx / self.image_width as f64 - 0.5) * 2.0 * PI;
+        let elevation = (0.5 - fy / self.image_height as f64) * PI;
+        let cos_elevation = elevation.cos();
+        self.to_world(Vec3::new(
+            cos_elevation * phi.sin(),
+            elevation.sin(),
+            -cos_elevation * phi.cos(),
+        ))
+    }
 
-        Ray::new(ray_origin, ray_direction)
+    /// 立方体贴图下图像坐标 (fx, fy) 对应的射线方向。
+    fn cube_map_direction(&self, fx: f64, fy: f64) -> Vec3 {
+        let face_width = self.image_width as f64 / 6.0;
+        let face = ((fx / face_width) as usize).min(5);
+        // 面内的坐标，s 向右、t 向下，范围为 [-1, 1]
+        let s = 2.0 * (fx - face as f64 * face_width) / face_width - 1.0;
+        let t = 2.0 * fy / self.image_height as f64 - 1.0;
+        let local = match face {
+            0 => Vec3::new(1.0, -t, -s),
+            1 => Vec3::new(-1.0, -t, s),
+            2 => Vec3::new(s, 1.0, t),
+            3 => Vec3::new(s, -1.0, -t),
+            4 => Vec3::new(s, -t, 1.0),
+            _ => Vec3::new(-s, -t, -1.0),
+        };
+        self.to_world(local)
     }
 
     /// 生成一个随机偏移量，用于抗锯齿。
@@ -255,12 +506,118 @@ impl Camera {
         self.center + vec.x * self.defocus_disk_u + vec.y * self.defocus_disk_v
     }
 
+    /// 将从镜头上 `origin` 出发、沿 `direction` 方向的射线投影到焦平面的图像上。
+    ///
+    /// # 返回值
+    ///
+    /// 射线落在图像内时，返回对应的像素坐标以及射线与相机朝向夹角的余弦；否则返回 None。
+    fn project(&self, origin: Point3, direction: Vec3) -> Option<(i32, i32, f64)> {
+        let cos_theta = -direction.unit_vector().dot(self.w);
+        if cos_theta <= 0.0 {
+            return None;
+        }
+
+        // 求出射线与焦平面的交点，再换算到像素坐标
+        let t = self.focus_dist / -direction.dot(self.w);
+        let offset = origin + t * direction - self.viewport_up_left;
+        let fx = offset.dot(self.pixel_delta_u) / self.pixel_delta_u.length_squared();
+        let fy = offset.dot(self.pixel_delta_v) / self.pixel_delta_v.length_squared();
+        if fx < 0.0 || fy < 0.0 || fx >= self.image_width as f64 || fy >= self.image_height as f64 {
+            return None;
+        }
+
+        Some((fx as i32, fy as i32, cos_theta))
+    }
+
+    /// 相机能否计算重要性，即光源子路径能否直接连接到相机上。
+    ///
+    /// 只有透视投影的相机可以；正交投影的射线方向是 delta 分布，无法被随机连接到，
+    /// 鱼眼和全景投影则没有实现重要性的计算。
+    pub fn supports_importance(&self) -> bool {
+        self.projection == Projection::Perspective
+    }
+
+    /// 相机从镜头上 `origin` 出发生成沿 `direction` 方向射线的立体角概率密度。
+    ///
+    /// 焦平面上的采样点在整个图像上均匀分布，因此概率密度为 `d² / (A * cos³θ)`，
+    /// 其中 d 为焦距，A 为焦平面上图像的面积。不支持重要性的相机返回 0。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::camera::{CameraBuilder, Projection};
+    /// use ray_tracing_in_one_weekend::{Point3, Vec3};
+    ///
+    /// // 默认的相机位于原点、朝向 -Z，距离为 1 的平面上图像占据 [-2, 2] × [-1, 1]
+    /// let camera = CameraBuilder::default()
+    ///     .aspect_ratio(2.0)
+    ///     .image_width(200)
+    ///     .vfov(90.0)
+    ///     .build();
+    ///
+    /// // 在该平面上用中点法积分，立体角元 dω = cos³θ dA，网格的边界与图像的边界对齐
+    /// let n = 300;
+    /// let cell = 6.0 / n as f64;
+    /// let mut total = 0.0;
+    /// for i in 0..n {
+    ///     for j in 0..n {
+    ///         let x = -3.0 + (i as f64 + 0.5) * cell;
+    ///         let y = -3.0 + (j as f64 + 0.5) * cell;
+    ///         let direction = Vec3::new(x, y, -1.0);
+    ///         let pdf = camera.importance_pdf(Point3::zero(), direction);
+    ///         total += pdf * cell * cell / direction.length().powi(3);
+    ///     }
+    /// }
+    /// // 概率密度在整个图像上积分为 1
+    /// assert!((total - 1.0).abs() < 1e-6, "{total}");
+    ///
+    /// // 正交投影不支持重要性
+    /// let ortho = CameraBuilder::default()
+    ///     .projection(Projection::Orthographic { view_height: 2.0 })
+    ///     .build();
+    /// assert_eq!(ortho.importance_pdf(Point3::zero(), -Vec3::unit_z()), 0.0);
+    /// ```
+    pub fn importance_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        if !self.supports_importance() {
+            return 0.0;
+        }
+        match self.project(origin, direction) {
+            Some((_, _, cos_theta)) => {
+                self.focus_dist * self.focus_dist / (self.viewport_area * cos_theta.powi(3))
+            }
+            None => 0.0,
+        }
+    }
+
+    /// 从场景中的点 `p` 向相机采样，得到镜头上的采样点以及对应的像素。
+    ///
+    /// 不支持重要性的相机返回 None。
+    pub fn sample_importance(&self, p: Point3) -> Option<CameraSample> {
+        if !self.supports_importance() {
+            return None;
+        }
+        let lens_point = if self.defocus_angle <= 0.0 {
+            self.center
+        } else {
+            self.defocus_disk_sample()
+        };
+
+        let direction = p - lens_point;
+        let (x, y, _) = self.project(lens_point, direction)?;
+        Some(CameraSample {
+            lens_point,
+            x,
+            y,
+            importance: self.importance_pdf(lens_point, direction),
+        })
+    }
+
     /// 渲染场景并输出图像。
     ///
     /// # 参数
     ///
     /// * `world` - 场景中的可命中对象。
-    pub fn render(&self, world: &dyn Hittable) {
+    /// * `lights` - 场景中可以被显式采样的光源，其中的物体也需要加入 `world`。
+    pub fn render(&self, world: &dyn Hittable, lights: &LightList) {
         // 开始渲染
         println!(
             "{}\n{} {}\n{}",
@@ -270,19 +627,32 @@ impl Camera {
             Self::MAX_COLOR_VALUE
         );
 
-        let mut stdout = std::io::stdout();
+        // 每一轮为所有像素各生成一条射线，把得到的颜色累加到胶片上
+        let mut film = Film::new(self.image_width, self.image_height);
+        for pass in 0..self.samples_per_pixel {
+            eprint!(
+                "\rSample passes remaining: {:>3}",
+                self.samples_per_pixel - pass
+            );
+            self.integrator.begin_pass(pass, world, lights);
+            for y in 0..self.image_height {
+                for x in 0..self.image_width {
+                    let Some(ray) = self.get_ray(x, y) else {
+                        continue;
+                    };
+                    let color = self
+                        .integrator
+                        .camera_ray_color(ray, self, &mut film, world, lights);
+                    film.add(x, y, color);
+                }
+            }
+        }
 
+        // 对累加的颜色取平均值后输出
+        let mut stdout = std::io::stdout();
         for y in 0..self.image_height {
-            eprint!("\rScan lines remaining: {:>3}", self.image_height - y);
             for x in 0..self.image_width {
-                // 生成多条射线，对得到的颜色取平均值
-                let mut color = Color::zero();
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(x, y);
-                    color += self.ray_color(ray, world, self.max_depth);
-                }
-                color *= self.samples_per_scale;
-
+                let color = film.get(x, y) * self.samples_per_scale;
                 write_color(&mut stdout, color).expect("Failed to write color to stdout");
             }
         }
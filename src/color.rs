@@ -10,6 +10,12 @@ pub type Color = Vec3;
 
 const INTENSITY: Interval = Interval::new(0.0, 0.999);
 
+/// 颜色的相对亮度（线性 sRGB 下的 Y 分量）
+#[inline]
+pub fn luminance(color: Color) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
 /// 将颜色输出到流中。
 pub fn write_color<W: Write>(out: &mut W, color: Color) -> std::io::Result<()> {
     // 获取 r, g, b (假设 Vec3 的 x, y, z 对应 r, g, b)
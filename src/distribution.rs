@@ -0,0 +1,156 @@
+//! 分段常数概率分布，用于按照亮度等权重对离散的单元进行重要性采样。
+
+/// 一维分段常数分布，定义在 [0, 1) 上，每一段的概率密度与对应的函数值成正比。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::distribution::Distribution1D;
+///
+/// let distribution = Distribution1D::new(vec![1.0, 3.0]);
+/// let (x, pdf, offset) = distribution.sample(0.5);
+/// assert_eq!(offset, 1);
+/// assert!((x - 2.0 / 3.0).abs() < 1e-12);
+/// assert_eq!(pdf, 1.5);
+/// ```
+pub struct Distribution1D {
+    /// 每一段的函数值
+    func: Vec<f64>,
+    /// 累积分布函数，长度比 `func` 多一
+    cdf: Vec<f64>,
+    /// 函数在 [0, 1) 上的积分
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// 由非负的函数值创建分布，函数值全为零时退化为均匀分布。
+    pub fn new(func: Vec<f64>) -> Self {
+        let n = func.len();
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + f.max(0.0) / n as f64);
+        }
+
+        let integral = cdf[n];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = if integral > 0.0 {
+                *c / integral
+            } else {
+                i as f64 / n as f64
+            };
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    /// 分段的数量
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    /// 函数在 [0, 1) 上的积分
+    #[inline]
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// 由 [0, 1) 内的随机数 `u` 采样。
+    ///
+    /// # 返回值
+    ///
+    /// 采样点、该点的概率密度以及采样点所在分段的序号。
+    pub fn sample(&self, u: f64) -> (f64, f64, usize) {
+        let n = self.count();
+        // 最后一个累积分布值不超过 u 的分段
+        let offset = self.cdf.partition_point(|&c| c <= u).clamp(1, n) - 1;
+
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0.0 {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.0
+        };
+        let x = ((offset as f64 + du) / n as f64).min(1.0 - f64::EPSILON);
+        (x, self.pdf_at(offset), offset)
+    }
+
+    /// 采样到点 `x` 的概率密度。
+    pub fn pdf(&self, x: f64) -> f64 {
+        let n = self.count();
+        let offset = ((x * n as f64) as usize).min(n.saturating_sub(1));
+        self.pdf_at(offset)
+    }
+
+    /// 第 `offset` 段内的概率密度
+    fn pdf_at(&self, offset: usize) -> f64 {
+        if self.integral > 0.0 {
+            self.func[offset].max(0.0) / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+/// 二维分段常数分布，定义在 [0, 1)² 上，先按边缘分布采样行，再按该行的条件分布采样列。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::distribution::Distribution2D;
+/// use ray_tracing_in_one_weekend::utils::random_double;
+///
+/// let func = [1.0, 2.0, 0.0, 4.0, 0.5, 3.0];
+/// let distribution = Distribution2D::new(&func, 3, 2);
+/// for _ in 0..100 {
+///     let ((x, y), pdf) = distribution.sample(random_double(), random_double());
+///     assert!(pdf > 0.0);
+///     assert!((distribution.pdf(x, y) - pdf).abs() < 1e-12);
+/// }
+/// // 概率密度与函数值成正比，函数的平均值为 10.5 / 6
+/// assert_eq!(distribution.pdf(0.9, 0.1), 0.0);
+/// assert!((distribution.pdf(0.1, 0.9) - 4.0 / (10.5 / 6.0)).abs() < 1e-12);
+/// ```
+pub struct Distribution2D {
+    /// 每一行内的条件分布
+    conditional: Vec<Distribution1D>,
+    /// 各行的边缘分布
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// 由按行优先顺序排列的 `width * height` 个非负函数值创建分布。
+    pub fn new(func: &[f64], width: usize, height: usize) -> Self {
+        assert_eq!(func.len(), width * height, "函数值数量与分布尺寸不符");
+        let conditional: Vec<Distribution1D> = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|d| d.integral()).collect());
+        Self {
+            conditional,
+            marginal,
+        }
+    }
+
+    /// 由 [0, 1) 内的两个随机数采样。
+    ///
+    /// # 返回值
+    ///
+    /// 采样点 (x, y)，其中 y 对应行，以及该点的概率密度。
+    pub fn sample(&self, u1: f64, u2: f64) -> ((f64, f64), f64) {
+        let (y, pdf_y, row) = self.marginal.sample(u2);
+        let (x, pdf_x, _) = self.conditional[row].sample(u1);
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    /// 采样到点 (x, y) 的概率密度。
+    pub fn pdf(&self, x: f64, y: f64) -> f64 {
+        let rows = self.marginal.count();
+        let row = ((y * rows as f64) as usize).min(rows.saturating_sub(1));
+        self.marginal.pdf(y) * self.conditional[row].pdf(x)
+    }
+}
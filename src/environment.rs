@@ -0,0 +1,183 @@
+//! 环境贴图光源，使用等距柱状投影的 HDR 图像照亮整个场景。
+
+use crate::color::{Color, luminance};
+use crate::distribution::Distribution2D;
+use crate::image::Image;
+use crate::light::{Light, LightSample};
+use crate::ray::{Point3, Ray};
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+/// 环境贴图光源
+///
+/// 射线逃逸出场景时按照方向在等距柱状投影的图像上查找辐亮度，取代默认的渐变背景。
+/// 作为光源被采样时，按照像素亮度与其所占立体角的乘积构建二维分段常数分布进行重要性采样，
+/// 因此图像中的太阳等明亮的小区域也能很快收敛。
+///
+/// 图像的水平方向对应方位角，顶部对应 +y 方向，底部对应 -y 方向，
+/// 与球体的表面坐标 (u, v) 使用相同的约定。
+///
+/// # 示例
+/// ```no_run
+/// use ray_tracing_in_one_weekend::environment::EnvironmentLight;
+/// use ray_tracing_in_one_weekend::image::Image;
+/// use ray_tracing_in_one_weekend::light::LightList;
+///
+/// let image = Image::load("studio.hdr").unwrap();
+/// let mut lights = LightList::new();
+/// lights.add(Box::new(EnvironmentLight::new(image).rotation(90.0)));
+/// ```
+///
+/// 只有一个明亮像素的环境贴图，采样几乎总是落在这个像素所在的方向上：
+/// ```
+/// use std::f64::consts::PI;
+/// use ray_tracing_in_one_weekend::environment::EnvironmentLight;
+/// use ray_tracing_in_one_weekend::image::Image;
+/// use ray_tracing_in_one_weekend::light::Light;
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// let (width, height) = (32, 16);
+/// let (bright_x, bright_y) = (8, 7);
+/// let mut pixels = vec![Color::new(0.01, 0.01, 0.01); width * height];
+/// pixels[bright_y * width + bright_x] = Color::new(100.0, 100.0, 100.0);
+/// let light = EnvironmentLight::new(Image::new(width, height, pixels));
+///
+/// // 明亮像素中心对应的方向
+/// let u = (bright_x as f64 + 0.5) / width as f64;
+/// let v = 1.0 - (bright_y as f64 + 0.5) / height as f64;
+/// let (theta, phi) = (v * PI, 2.0 * PI * u - PI);
+/// let bright = Vec3::new(theta.sin() * phi.cos(), -theta.cos(), -theta.sin() * phi.sin());
+/// let p = Point3::zero();
+/// assert!((light.background(Ray::new(p, bright)).x - 100.0).abs() < 1e-9);
+///
+/// let n = 1000;
+/// let mut near = 0;
+/// for _ in 0..n {
+///     let sample = light.sample(p).unwrap();
+///     let pdf = light.pdf(p, sample.direction);
+///     assert!((sample.pdf - pdf).abs() <= 1e-6 * pdf);
+///     if sample.direction.dot(bright) > 30.0f64.to_radians().cos() {
+///         near += 1;
+///     }
+/// }
+/// assert!(near > n * 9 / 10);
+/// ```
+pub struct EnvironmentLight {
+    /// 等距柱状投影的环境图像
+    image: Image,
+    /// 在图像的 (u, 1 - v) 坐标上采样的分布，行对应图像的行
+    distribution: Distribution2D,
+    /// 辐亮度的整体缩放
+    scale: f64,
+    /// 环境绕 y 轴旋转的角度，单位为弧度
+    rotation: f64,
+}
+
+impl EnvironmentLight {
+    /// 由等距柱状投影的图像创建一个新的环境贴图光源实例，图像的宽和高都不能为零。
+    pub fn new(image: Image) -> Self {
+        let (width, height) = (image.width(), image.height());
+        assert!(width > 0 && height > 0, "环境贴图的尺寸不能为零");
+
+        // 查找辐亮度时使用双线性插值，每个像素的权重取周围 3x3 像素中的最大亮度，
+        // 保证辐亮度不为零的方向都能被采样到；再乘以 sinθ 补偿两极附近像素较小的立体角
+        let mut func = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sin_theta = ((y as f64 + 0.5) / height as f64 * PI).sin();
+            for x in 0..width {
+                let mut max = 0.0f64;
+                for dy in [-1, 0, 1] {
+                    let ny = (y as i64 + dy).clamp(0, height as i64 - 1) as usize;
+                    for dx in [-1, 0, 1] {
+                        let nx = (x as i64 + dx).rem_euclid(width as i64) as usize;
+                        max = max.max(luminance(image.pixel(nx, ny)));
+                    }
+                }
+                func.push(max * sin_theta);
+            }
+        }
+
+        Self {
+            distribution: Distribution2D::new(&func, width, height),
+            image,
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// 设置辐亮度的整体缩放，默认为 1。
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// 设置环境绕 y 轴旋转的角度，单位为角度，默认为 0。
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees.to_radians();
+        self
+    }
+
+    /// 把单位方向向量转换为图像上的 (u, v) 坐标以及 sinθ。
+    fn direction_to_uv(&self, w: Vec3) -> (f64, f64, f64) {
+        let theta = (-w.y).clamp(-1.0, 1.0).acos();
+        let phi = (-w.z).atan2(w.x) + PI - self.rotation;
+        ((phi / (2.0 * PI)).rem_euclid(1.0), theta / PI, theta.sin())
+    }
+
+    /// 把图像上的 (u, v) 坐标转换为单位方向向量以及 sinθ。
+    fn uv_to_direction(&self, u: f64, v: f64) -> (Vec3, f64) {
+        let theta = v * PI;
+        let phi = 2.0 * PI * u - PI + self.rotation;
+        let sin_theta = theta.sin();
+        (
+            Vec3::new(sin_theta * phi.cos(), -theta.cos(), -sin_theta * phi.sin()),
+            sin_theta,
+        )
+    }
+
+    /// (u, v) 坐标上的概率密度转换为立体角概率密度：dω = 2π² sinθ du dv
+    fn solid_angle_pdf(pdf_uv: f64, sin_theta: f64) -> f64 {
+        if sin_theta <= 0.0 {
+            0.0
+        } else {
+            pdf_uv / (2.0 * PI * PI * sin_theta)
+        }
+    }
+}
+
+impl Light for EnvironmentLight {
+    fn sample(&self, _: Point3) -> Option<LightSample> {
+        let ((u, t), pdf_uv) = self.distribution.sample(random_double(), random_double());
+        // 分布的行从图像顶部开始，而 v = 0 对应图像底部
+        let v = 1.0 - t;
+        let (direction, sin_theta) = self.uv_to_direction(u, v);
+        let pdf = Self::solid_angle_pdf(pdf_uv, sin_theta);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            radiance: self.scale * self.image.sample(u, v),
+            pdf,
+            normal: None,
+            is_delta: false,
+        })
+    }
+
+    fn pdf(&self, _: Point3, direction: Vec3) -> f64 {
+        let (u, v, sin_theta) = self.direction_to_uv(direction.unit_vector());
+        Self::solid_angle_pdf(self.distribution.pdf(u, 1.0 - v), sin_theta)
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
+    fn background(&self, r: Ray) -> Color {
+        let (u, v, _) = self.direction_to_uv(r.direction.unit_vector());
+        self.scale * self.image.sample(u, v)
+    }
+}
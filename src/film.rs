@@ -0,0 +1,45 @@
+//! 胶片（图像缓冲区）的定义，用于在输出之前累加每个像素的颜色。
+
+use crate::color::Color;
+
+/// 胶片，按行优先的顺序保存每个像素累加的颜色。
+pub struct Film {
+    width: i32,
+    height: i32,
+    pixels: Vec<Color>,
+}
+
+impl Film {
+    /// 创建一个新的全黑胶片实例。
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::zero(); (width * height) as usize],
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// 向指定像素累加颜色，坐标超出胶片范围时忽略。
+    #[inline]
+    pub fn add(&mut self, x: i32, y: i32, color: Color) {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            self.pixels[(y * self.width + x) as usize] += color;
+        }
+    }
+
+    /// 获取指定像素累加的颜色。
+    #[inline]
+    pub fn get(&self, x: i32, y: i32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
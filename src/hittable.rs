@@ -1,7 +1,9 @@
 //! 光线与物体碰撞检测相关逻辑，包含碰撞记录、Hittable Trait。
 
+use crate::aabb::Aabb;
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::{Point3, Ray};
 use crate::vec3::Vec3;
 
@@ -18,17 +20,32 @@ pub struct HitRecord<'a> {
     pub front_face: bool,
     /// 碰撞时的材质
     pub mat: &'a dyn Material,
+    /// 被击中的物体，次表面散射等材质需要用它计算光线离开物体的位置，可以通过 [`HitRecord::with_object`] 设置
+    pub object: Option<&'a dyn Hittable>,
+    /// 物体外侧（包围该物体的）介质的折射率，默认为空气，嵌套电介质时由介质栈填写
+    pub exterior_ior: f64,
+    /// 碰撞点的表面坐标 u
+    pub u: f64,
+    /// 碰撞点的表面坐标 v
+    pub v: f64,
+    /// 碰撞点沿表面坐标 u 方向的切线 `∂p/∂u`，与 `dpdv` 一起构成切线空间
+    pub dpdu: Vec3,
+    /// 碰撞点沿表面坐标 v 方向的切线 `∂p/∂v`
+    pub dpdv: Vec3,
 }
 
 impl<'a> HitRecord<'a> {
     /// 创建一个新的碰撞记录实例。
     ///
+    /// 表面坐标默认为 0，切线为与法线垂直的任意一组正交单位向量，可以通过 [`HitRecord::with_uv`] 设置。
+    ///
     /// # 参数
     ///
     /// * `p` - 碰撞点
     /// * `output_normal` - 物体的几何法线（始终指向外），要求是单位向量
     /// * `t` - 碰撞时间
     /// * `ray` - 碰撞时的光线
+    /// * `mat` - 碰撞点的材质
     pub fn new(p: Point3, output_normal: Vec3, t: f64, ray: Ray, mat: &'a dyn Material) -> Self {
         let front_face = ray.direction.dot(output_normal) < 0.0;
         // 在 front_face 为 false 时，翻转法线向量，存储最终的法线向量
@@ -38,18 +55,74 @@ impl<'a> HitRecord<'a> {
             -output_normal
         };
 
+        // 任选一组切线，使得 dpdu × dpdv 指向物体外侧
+        let dpdu = Onb::new(output_normal).u();
+        let dpdv = Vec3::cross(output_normal, dpdu);
+
         Self {
             p,
             normal,
             t,
             front_face,
             mat,
+            object: None,
+            exterior_ior: 1.0,
+            u: 0.0,
+            v: 0.0,
+            dpdu,
+            dpdv,
         }
     }
+
+    /// 设置碰撞点的表面坐标以及对应的切线。
+    pub fn with_uv(mut self, u: f64, v: f64, dpdu: Vec3, dpdv: Vec3) -> Self {
+        self.u = u;
+        self.v = v;
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// 设置被击中的物体。
+    pub fn with_object(mut self, object: &'a dyn Hittable) -> Self {
+        self.object = Some(object);
+        self
+    }
 }
 
 /// 碰撞检测接口
 pub trait Hittable {
     /// 检测光线在给定时间范围内能否与物体发生碰撞
     fn hit(&self, r: Ray, interval: Interval) -> Option<HitRecord<'_>>;
+
+    /// 从 `origin` 出发朝 `direction` 方向看向物体时，该方向对应的立体角概率密度。
+    ///
+    /// 只有能够作为光源被采样的物体需要实现该方法，默认返回 0。
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// 从 `origin` 出发，随机生成一个指向物体的方向，其分布与 [`Hittable::pdf_value`] 一致。
+    fn random(&self, _origin: Point3) -> Vec3 {
+        Vec3::unit_x()
+    }
+
+    /// 在物体表面上按面积均匀地随机采样一个点。
+    ///
+    /// # 返回值
+    ///
+    /// 采样点的碰撞记录，其中法线朝外且 `front_face` 为 true；物体不支持表面采样时返回 None。
+    fn sample_surface(&self) -> Option<HitRecord<'_>> {
+        None
+    }
+
+    /// 物体的表面积，与 [`Hittable::sample_surface`] 配合使用。
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    /// 物体的轴对齐包围盒，物体无限大或者无法确定范围时返回 None。
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
 }
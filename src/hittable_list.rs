@@ -1,6 +1,9 @@
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
-use crate::ray::Ray;
+use crate::ray::{Point3, Ray};
+use crate::utils::random_int;
+use crate::vec3::Vec3;
 
 pub struct HittableList {
     /// 物体列表
@@ -27,6 +30,12 @@ impl HittableList {
     }
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Hittable for HittableList {
     fn hit(&self, r: Ray, interval: Interval) -> Option<HitRecord<'_>> {
         let mut hit_record = None;
@@ -40,4 +49,33 @@ impl Hittable for HittableList {
 
         hit_record
     }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        // 每个物体被选中的概率相同，因此总的概率密度是各个物体概率密度的平均值
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::unit_x();
+        }
+
+        let index = random_int(0, self.objects.len() as i32 - 1) as usize;
+        self.objects[index].random(origin)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // 任何一个物体没有包围盒时，整个列表也没有
+        let mut boxes = self.objects.iter().map(|object| object.bounding_box());
+        let first = boxes.next()??;
+        boxes.try_fold(first, |acc, b| Some(acc.union(b?)))
+    }
 }
@@ -0,0 +1,417 @@
+//! IES LM-63 配光曲线文件的解析，以及按照配光曲线发光的光源。
+
+use crate::light::{EmissionSample, Light, LightSample, PointLight, SpotLight};
+use crate::light_bvh::LightBounds;
+use crate::onb::Onb;
+use crate::ray::Point3;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+use std::io;
+use std::path::Path;
+
+/// IES LM-63 格式的配光曲线，记录灯具沿各个方向的发光强度（坎德拉）。
+///
+/// 只支持建筑照明常用的 C 类配光：垂直角 0° 为灯具正下方，180° 为正上方，
+/// 水平角绕灯具的竖直轴旋转。水平角只覆盖 0°、0°~90°、0°~180° 或 90°~270° 时，
+/// 按照文件约定的对称性补全其余方向。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::ies::IesProfile;
+///
+/// let profile = IesProfile::load("assets/downlight.ies").unwrap();
+/// assert_eq!(profile.max_candela(), 800.0);
+/// assert_eq!(profile.candela(20.0, 0.0), 720.0);
+/// // 轴对称的配光与水平角无关，垂直角之间线性插值
+/// assert_eq!(profile.candela(25.0, 123.0), 670.0);
+/// // 上半球没有数据，不发光
+/// assert_eq!(profile.candela(120.0, 0.0), 0.0);
+/// ```
+pub struct IesProfile {
+    /// 递增的垂直角，单位为角度
+    vertical_angles: Vec<f64>,
+    /// 递增的水平角，单位为角度
+    horizontal_angles: Vec<f64>,
+    /// 发光强度，按水平角分组，每组依次对应各个垂直角
+    candela: Vec<f64>,
+    /// 最大发光强度
+    max_candela: f64,
+}
+
+impl IesProfile {
+    /// 从文件中读取配光曲线。
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::parse(&String::from_utf8_lossy(&data))
+    }
+
+    /// 解析 IES LM-63（1986、1991、1995 或 2002 版）格式的配光曲线。
+    ///
+    /// 发光强度已经乘以文件中的光强倍数和镇流器系数；灯具倾斜数据（TILT）会被忽略。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::ies::IesProfile;
+    ///
+    /// let profile = IesProfile::load("assets/wallwash.ies").unwrap();
+    /// // 光强倍数为 2
+    /// assert_eq!(profile.max_candela(), 400.0);
+    /// assert_eq!(profile.candela(45.0, 90.0), 240.0);
+    /// // 水平角只覆盖 0°~180°，另一半关于 0°~180° 平面对称
+    /// assert_eq!(profile.candela(45.0, 270.0), 240.0);
+    /// assert_eq!(profile.candela(45.0, 315.0), profile.candela(45.0, 45.0));
+    ///
+    /// assert!(IesProfile::parse("IESNA:LM-63-2002\nTILT=NONE\n1 1000 1").is_err());
+    /// ```
+    pub fn parse(text: &str) -> io::Result<Self> {
+        // 跳过文件头和关键字行，直到 TILT 行
+        let mut lines = text.lines();
+        let tilt = loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| invalid_data("IES 文件缺少 TILT 行"))?
+                .trim();
+            if let Some(tilt) = line.strip_prefix("TILT")
+                && let Some(tilt) = tilt.trim_start().strip_prefix('=')
+            {
+                break tilt.trim();
+            }
+        };
+
+        // 其余部分是以空白或逗号分隔的数字，可以任意换行
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| invalid_data("IES 文件中的数字无效"))
+            });
+        let mut next = || {
+            numbers
+                .next()
+                .unwrap_or_else(|| Err(invalid_data("IES 数据不完整")))
+        };
+
+        if tilt == "INCLUDE" {
+            // 灯具几何类型、角度与倍数的对数，以及对应的角度和倍数
+            next()?;
+            let pairs = count(next()?)?;
+            for _ in 0..2 * pairs {
+                next()?;
+            }
+        }
+
+        // 灯的数量、每个灯的光通量
+        next()?;
+        next()?;
+        let multiplier = next()?;
+        let vertical_count = count(next()?)?;
+        let horizontal_count = count(next()?)?;
+        let photometric_type = next()?;
+        // 尺寸单位以及灯具的宽、长、高
+        for _ in 0..4 {
+            next()?;
+        }
+        let ballast_factor = next()?;
+        // 保留字段（旧版本中为镇流器-灯光度系数）和输入功率
+        next()?;
+        next()?;
+
+        if photometric_type != 1.0 {
+            return Err(invalid_data("只支持 C 类配光"));
+        }
+
+        let vertical_angles = (0..vertical_count)
+            .map(|_| next())
+            .collect::<io::Result<Vec<_>>>()?;
+        let horizontal_angles = (0..horizontal_count)
+            .map(|_| next())
+            .collect::<io::Result<Vec<_>>>()?;
+        let candela = (0..vertical_count * horizontal_count)
+            .map(|_| next().map(|c| c * multiplier * ballast_factor))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let increasing = |angles: &[f64]| angles.windows(2).all(|w| w[0] < w[1]);
+        if !increasing(&vertical_angles) || !increasing(&horizontal_angles) {
+            return Err(invalid_data("IES 文件中的角度不是递增的"));
+        }
+
+        let max_candela = candela.iter().fold(0.0f64, |max, &c| max.max(c));
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            max_candela,
+        })
+    }
+
+    /// 最大发光强度，单位为坎德拉
+    #[inline]
+    pub fn max_candela(&self) -> f64 {
+        self.max_candela
+    }
+
+    /// 沿给定方向的发光强度，角度单位为度，在相邻的数据之间双线性插值。
+    ///
+    /// 垂直角超出文件数据范围的方向不发光。
+    pub fn candela(&self, vertical: f64, horizontal: f64) -> f64 {
+        let Some((v0, v1, tv)) = locate(&self.vertical_angles, vertical) else {
+            return 0.0;
+        };
+        let Some((h0, h1, th)) = self.locate_horizontal(horizontal) else {
+            return 0.0;
+        };
+
+        let count = self.vertical_angles.len();
+        let at = |h: usize, v: usize| self.candela[h * count + v];
+        (1.0 - th) * ((1.0 - tv) * at(h0, v0) + tv * at(h0, v1))
+            + th * ((1.0 - tv) * at(h1, v0) + tv * at(h1, v1))
+    }
+
+    /// 按照配光的对称性把水平角映射到文件数据的范围内，再查找所在的区间。
+    fn locate_horizontal(&self, horizontal: f64) -> Option<(usize, usize, f64)> {
+        let angles = &self.horizontal_angles;
+        let (first, last) = (angles[0], angles[angles.len() - 1]);
+        let phi = horizontal.rem_euclid(360.0);
+
+        if angles.len() == 1 {
+            // 轴对称
+            return Some((0, 0, 0.0));
+        }
+        let phi = if first == 0.0 && last == 90.0 {
+            // 四个象限对称
+            let phi = if phi > 180.0 { 360.0 - phi } else { phi };
+            if phi > 90.0 { 180.0 - phi } else { phi }
+        } else if first == 0.0 && last == 180.0 {
+            // 关于 0°~180° 平面对称
+            if phi > 180.0 { 360.0 - phi } else { phi }
+        } else if first == 90.0 && last == 270.0 {
+            // 关于 90°~270° 平面对称
+            if phi < 90.0 {
+                180.0 - phi
+            } else if phi > 270.0 {
+                540.0 - phi
+            } else {
+                phi
+            }
+        } else {
+            // 完整的 360°，最后一个水平角与第一个水平角之间绕回插值
+            let phi = if phi < first { phi + 360.0 } else { phi };
+            if phi > last {
+                let t = (phi - last) / (first + 360.0 - last);
+                return Some((angles.len() - 1, 0, t));
+            }
+            phi
+        };
+        locate(angles, phi)
+    }
+
+    /// 归一化（最大值为 1）的发光强度在整个球面上的平均值，用于估计光源的功率。
+    fn average(&self) -> f64 {
+        if self.max_candela <= 0.0 {
+            return 0.0;
+        }
+
+        // 在垂直角和水平角上等分取中点，按 sinθ 加权
+        const THETA_STEPS: usize = 90;
+        const PHI_STEPS: usize = 180;
+        let mut sum = 0.0;
+        for i in 0..THETA_STEPS {
+            let theta = (i as f64 + 0.5) / THETA_STEPS as f64 * 180.0;
+            let sin_theta = theta.to_radians().sin();
+            for j in 0..PHI_STEPS {
+                let phi = (j as f64 + 0.5) / PHI_STEPS as f64 * 360.0;
+                sum += self.candela(theta, phi) * sin_theta;
+            }
+        }
+        let d_omega = (PI / THETA_STEPS as f64) * (2.0 * PI / PHI_STEPS as f64);
+        sum * d_omega / (4.0 * PI) / self.max_candela
+    }
+}
+
+/// 按照 IES 配光曲线调制发光强度的光源
+///
+/// 包装一个点光源或聚光灯，光源沿各个方向的发光强度乘以配光曲线在该方向上
+/// 归一化的发光强度（最大值为 1），因此被包装光源的强度就是配光曲线最亮方向上的强度。
+/// 配光曲线的垂直角 0° 对应光源的朝向，水平角绕朝向旋转：水平角 0°（C0 平面）指向参考方向
+/// 在垂直于朝向的平面上的投影，默认的参考方向为世界坐标的 x 轴（朝向与 x 轴平行时为 z 轴），
+/// 可以通过 [`IesLight::reference`] 修改；逆着朝向看去（例如从上方俯视向下的灯具），水平角沿逆时针方向增大。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::color::Color;
+/// use ray_tracing_in_one_weekend::ies::{IesLight, IesProfile};
+/// use ray_tracing_in_one_weekend::light::{Light, LightList, PointLight};
+/// use ray_tracing_in_one_weekend::ray::Point3;
+/// use ray_tracing_in_one_weekend::vec3::Vec3;
+///
+/// let profile = IesProfile::load("assets/downlight.ies").unwrap();
+/// let position = Point3::new(0.0, 3.0, 0.0);
+/// let light = PointLight::new(position, Color::new(10.0, 10.0, 10.0));
+///
+/// let mut lights = LightList::new();
+/// lights.add(Box::new(IesLight::point(light, Point3::zero(), profile)));
+///
+/// // 正下方不衰减，照度随距离的平方衰减
+/// let sample = lights.sample(Point3::zero()).unwrap();
+/// assert!((sample.radiance.x - 10.0 / 9.0).abs() < 1e-12);
+/// // 灯具上方不发光
+/// assert!(lights.sample(Point3::new(0.0, 5.0, 0.0)).is_none());
+///
+/// // 非对称的洗墙灯，C0 平面最亮，C180 平面最暗
+/// let wall_washer = |rotation: f64| {
+///     let light = PointLight::new(position, Color::new(18.0, 18.0, 18.0));
+///     let profile = IesProfile::load("assets/wallwash.ies").unwrap();
+///     IesLight::point(light, Point3::zero(), profile).rotation(rotation)
+/// };
+/// // 与正下方成 45° 的方向上，C0 默认朝向 +x
+/// let factor = |light: &IesLight, p: Point3| light.sample(p).unwrap().radiance.x;
+/// assert!((factor(&wall_washer(0.0), Point3::new(3.0, 0.0, 0.0)) - 1.0).abs() < 1e-12);
+/// assert!((factor(&wall_washer(0.0), Point3::new(-3.0, 0.0, 0.0)) - 0.3).abs() < 1e-12);
+/// // 从上方俯视时逆时针旋转 90°，C0 转到 -z
+/// assert!((factor(&wall_washer(90.0), Point3::new(0.0, 0.0, -3.0)) - 1.0).abs() < 1e-12);
+/// // 也可以直接指定 C0 的参考方向
+/// let toward_z = wall_washer(0.0).reference(Vec3::unit_z());
+/// assert!((factor(&toward_z, Point3::new(0.0, 0.0, 3.0)) - 1.0).abs() < 1e-12);
+/// ```
+pub struct IesLight {
+    /// 被包装的点光源或聚光灯
+    light: Box<dyn Light>,
+    /// 以光源朝向为 w 轴的坐标系，u 轴为水平角 0° 的方向，v 轴为水平角 270° 的方向
+    frame: Onb,
+    /// 配光曲线绕光源朝向旋转的角度，单位为度
+    rotation: f64,
+    /// 配光曲线
+    profile: IesProfile,
+}
+
+impl IesLight {
+    /// 由点光源创建一个朝向 `target` 的 IES 光源。
+    pub fn point(light: PointLight, target: Point3, profile: IesProfile) -> Self {
+        let frame = Self::frame(target - light.position, None);
+        Self {
+            light: Box::new(light),
+            frame,
+            rotation: 0.0,
+            profile,
+        }
+    }
+
+    /// 由聚光灯创建 IES 光源，配光曲线的朝向与聚光灯的圆锥轴线一致，
+    /// 发光强度同时受聚光灯圆锥和配光曲线的限制。
+    pub fn spot(light: SpotLight, profile: IesProfile) -> Self {
+        let frame = Self::frame(light.direction, None);
+        Self {
+            light: Box::new(light),
+            frame,
+            rotation: 0.0,
+            profile,
+        }
+    }
+
+    /// 设置水平角 0° 的参考方向，实际使用的是它在垂直于光源朝向的平面上的投影。
+    ///
+    /// 参考方向与光源朝向平行时使用默认的参考方向。
+    pub fn reference(mut self, reference: Vec3) -> Self {
+        self.frame = Self::frame(self.frame.w(), Some(reference));
+        self
+    }
+
+    /// 设置配光曲线绕光源朝向旋转的角度，单位为度，默认为 0。
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// 以 `direction` 为朝向、`reference` 的投影为水平角 0° 的坐标系。
+    fn frame(direction: Vec3, reference: Option<Vec3>) -> Onb {
+        let w = direction.unit_vector();
+        let default = if w.x.abs() > 0.999 {
+            Vec3::unit_z()
+        } else {
+            Vec3::unit_x()
+        };
+        let reference = reference
+            .filter(|r| !(*r - w * w.dot(*r)).near_zero())
+            .unwrap_or(default);
+        Onb::from_tangent(w, reference)
+    }
+
+    /// 沿从光源出发的单位向量 `w` 方向，配光曲线归一化的发光强度。
+    fn factor(&self, w: Vec3) -> f64 {
+        if self.profile.max_candela <= 0.0 {
+            return 0.0;
+        }
+        let local = self.frame.to_local(w);
+        let vertical = local.z.clamp(-1.0, 1.0).acos().to_degrees();
+        // v 轴为朝向 × C0，逆着朝向看去水平角逆时针增大时 C90 位于 -v 一侧
+        let horizontal = (-local.y).atan2(local.x).to_degrees() - self.rotation;
+        self.profile.candela(vertical, horizontal) / self.profile.max_candela
+    }
+}
+
+impl Light for IesLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let mut sample = self.light.sample(p)?;
+        let factor = self.factor(-sample.direction);
+        if factor <= 0.0 {
+            return None;
+        }
+        sample.radiance *= factor;
+        Some(sample)
+    }
+
+    fn pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.light.pdf(origin, direction)
+    }
+
+    fn is_delta(&self) -> bool {
+        self.light.is_delta()
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        let mut sample = self.light.sample_emission()?;
+        sample.radiance *= self.factor(sample.ray.direction);
+        Some(sample)
+    }
+
+    fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        self.light.pdf_emission(p, direction)
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        // 空间和方向范围沿用被包装的光源，功率按配光曲线的平均强度缩小
+        let mut bounds = self.light.bounds()?;
+        bounds.phi *= self.profile.average();
+        Some(bounds)
+    }
+}
+
+/// 在递增的角度序列中查找 `x` 所在的区间，返回区间两端的序号和插值权重，超出范围时返回 None。
+fn locate(angles: &[f64], x: f64) -> Option<(usize, usize, f64)> {
+    let n = angles.len();
+    if n == 1 {
+        return (x == angles[0]).then_some((0, 0, 0.0));
+    }
+    if x < angles[0] || x > angles[n - 1] {
+        return None;
+    }
+    let i = angles.partition_point(|&a| a <= x).clamp(1, n - 1) - 1;
+    let t = (x - angles[i]) / (angles[i + 1] - angles[i]);
+    Some((i, i + 1, t))
+}
+
+/// 把文件中的角度或数据个数转换为正整数。
+fn count(value: f64) -> io::Result<usize> {
+    if value >= 1.0 && value.fract() == 0.0 {
+        Ok(value as usize)
+    } else {
+        Err(invalid_data("IES 文件中的数量无效"))
+    }
+}
+
+/// 创建一个表示数据格式错误的 IO 错误。
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
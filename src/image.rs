@@ -0,0 +1,381 @@
+//! 图像纹理的定义，以及 PPM、PFM 和 Radiance HDR 格式图像的读取。
+
+use crate::color::Color;
+use std::io;
+use std::path::Path;
+
+/// 按行优先顺序保存的图像，第 0 行位于图像顶部。
+///
+/// PPM 图像的颜色分量归一化到 [0, 1]，PFM 和 HDR 图像保存的是线性的高动态范围数值。
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    /// 由像素数据创建一个新的图像实例，`pixels` 的长度必须为 `width * height`。
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "像素数量与图像尺寸不符");
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// 从文件中读取图像，根据文件开头的标识判断是 PPM、PFM 还是 Radiance HDR 格式。
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        match data.get(..2) {
+            Some(b"PF" | b"Pf") => Self::from_pfm(&data),
+            Some(b"#?") => Self::from_hdr(&data),
+            _ => Self::from_ppm(&data),
+        }
+    }
+
+    /// 解析 PPM 格式（文本格式 P3 或二进制格式 P6）的图像数据。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::image::Image;
+    ///
+    /// let image = Image::from_ppm(b"P3\n2 1\n255\n255 0 0  0 0 255\n").unwrap();
+    /// assert_eq!((image.width(), image.height()), (2, 1));
+    /// assert_eq!(image.pixel(1, 0).z, 1.0);
+    ///
+    /// // 尺寸无效时返回错误而不是崩溃
+    /// assert!(Image::from_ppm(b"P6\n99999999999 99999999999\n255\n").is_err());
+    /// assert!(Image::from_ppm(b"P3\n0 1\n255\n").is_err());
+    /// ```
+    pub fn from_ppm(data: &[u8]) -> io::Result<Self> {
+        let mut reader = PpmReader { data, pos: 0 };
+        let magic = reader.token()?;
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let max_value = reader.number()?;
+        if max_value == 0 || max_value > 65535 {
+            return Err(invalid_data("PPM 的最大颜色值无效"));
+        }
+        let count = pixel_count(width, height)?
+            .checked_mul(3)
+            .ok_or_else(|| invalid_data("图像尺寸过大"))?;
+
+        let values: Vec<usize> = match magic.as_str() {
+            "P3" => (0..count)
+                .map(|_| reader.number())
+                .collect::<io::Result<_>>()?,
+            "P6" => {
+                // 文件头与像素数据之间只有一个空白字符
+                let start = reader.pos + 1;
+                let bytes_per_value = if max_value < 256 { 1 } else { 2 };
+                let end = count
+                    .checked_mul(bytes_per_value)
+                    .and_then(|len| len.checked_add(start))
+                    .filter(|&end| end <= data.len())
+                    .ok_or_else(|| invalid_data("PPM 数据不完整"))?;
+                data[start..end]
+                    .chunks(bytes_per_value)
+                    .map(|chunk| chunk.iter().fold(0, |acc, &b| (acc << 8) | b as usize))
+                    .collect()
+            }
+            _ => return Err(invalid_data("只支持 P3 和 P6 格式的 PPM 图像")),
+        };
+
+        let scale = 1.0 / max_value as f64;
+        let pixels = values
+            .chunks(3)
+            .map(|c| Color::new(c[0] as f64, c[1] as f64, c[2] as f64) * scale)
+            .collect();
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// 解析 PFM 格式（彩色 PF 或灰度 Pf）的浮点图像数据。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::image::Image;
+    ///
+    /// let mut data = b"Pf\n1 2\n-1.0\n".to_vec();
+    /// // 数据从图像底部一行开始保存
+    /// data.extend(0.25f32.to_le_bytes());
+    /// data.extend(4.0f32.to_le_bytes());
+    /// let image = Image::from_pfm(&data).unwrap();
+    /// assert_eq!(image.pixel(0, 0).y, 4.0);
+    /// assert_eq!(image.pixel(0, 1).z, 0.25);
+    ///
+    /// assert!(Image::from_pfm(b"PF\n0 1\n-1.0\n").is_err());
+    /// assert!(Image::from_pfm(b"PF\n99999999999 99999999999\n-1.0\n").is_err());
+    /// ```
+    pub fn from_pfm(data: &[u8]) -> io::Result<Self> {
+        let mut reader = PpmReader { data, pos: 0 };
+        let channels = match reader.token()?.as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => return Err(invalid_data("只支持 PF 和 Pf 格式的 PFM 图像")),
+        };
+        let width = reader.number()?;
+        let height = reader.number()?;
+        // 比例因子为负数时数据为小端序
+        let scale: f64 = reader
+            .token()?
+            .parse()
+            .map_err(|_| invalid_data("PFM 中的比例因子无效"))?;
+
+        let start = reader.pos + 1;
+        let end = pixel_count(width, height)?
+            .checked_mul(channels * 4)
+            .and_then(|len| len.checked_add(start))
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| invalid_data("PFM 数据不完整"))?;
+        let values: Vec<f64> = data[start..end]
+            .chunks(4)
+            .map(|chunk| {
+                let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                let value = if scale < 0.0 {
+                    f32::from_le_bytes(bytes)
+                } else {
+                    f32::from_be_bytes(bytes)
+                };
+                value as f64
+            })
+            .collect();
+
+        // PFM 从底部一行开始保存，翻转为从顶部开始
+        let pixels = values
+            .chunks(width * channels)
+            .rev()
+            .flat_map(|row| row.chunks(channels))
+            .map(|c| match c {
+                [r, g, b] => Color::new(*r, *g, *b),
+                _ => Color::new(c[0], c[0], c[0]),
+            })
+            .collect();
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// 解析 Radiance HDR（RGBE）格式的图像数据，支持未压缩和按扫描线游程编码的数据。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::image::Image;
+    ///
+    /// let mut data = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n".to_vec();
+    /// data.extend([128, 64, 0, 129, 0, 0, 0, 0]);
+    /// let image = Image::from_hdr(&data).unwrap();
+    /// assert_eq!(image.pixel(0, 0).x, 1.0);
+    /// assert_eq!(image.pixel(1, 0).x, 0.0);
+    ///
+    /// assert!(Image::from_hdr(b"#?RADIANCE\n\n-Y 0 +X 0\n").is_err());
+    /// assert!(Image::from_hdr(b"#?RADIANCE\n\n-Y 99999999999 +X 99999999999\n").is_err());
+    /// ```
+    pub fn from_hdr(data: &[u8]) -> io::Result<Self> {
+        let mut reader = HdrReader { data, pos: 0 };
+        if !reader.line()?.starts_with("#?") {
+            return Err(invalid_data("不是 Radiance HDR 图像"));
+        }
+        // 文件头以空行结束
+        loop {
+            let line = reader.line()?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=")
+                && format != "32-bit_rle_rgbe"
+            {
+                return Err(invalid_data("只支持 RGBE 格式的 HDR 图像"));
+            }
+        }
+
+        // 只支持最常见的从上到下、从左到右的扫描顺序
+        let resolution = reader.line()?;
+        let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+            ["-Y", height, "+X", width] => (height.parse(), width.parse()),
+            _ => return Err(invalid_data("不支持的 HDR 扫描顺序")),
+        };
+        let (Ok(height), Ok(width)) = (height, width) else {
+            return Err(invalid_data("HDR 中的图像尺寸无效"));
+        };
+
+        // 像素只会随着读取的数据增加，预分配的空间不超过数据长度，避免尺寸过大时耗尽内存
+        let count = pixel_count(width, height)?;
+        let mut pixels = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..height {
+            let scanline = reader.scanline(width)?;
+            pixels.extend(scanline.iter().map(|&[r, g, b, e]| {
+                if e == 0 {
+                    return Color::zero();
+                }
+                let scale = 2f64.powi(e as i32 - 136);
+                Color::new(r as f64, g as f64, b as f64) * scale
+            }));
+        }
+        Ok(Self::new(width, height, pixels))
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// 获取指定像素的颜色。
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// 按照表面坐标双线性插值采样图像，超出 [0, 1] 的坐标会重复平铺。
+    ///
+    /// v = 0 对应图像底部，v = 1 对应图像顶部。
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        if self.pixels.is_empty() {
+            return Color::zero();
+        }
+        // 转换到以像素中心为整数点的连续坐标
+        let x = u.rem_euclid(1.0) * self.width as f64 - 0.5;
+        let y = (1.0 - v.rem_euclid(1.0)) * self.height as f64 - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+
+        let wrap = |i: f64, n: usize| (i as i64).rem_euclid(n as i64) as usize;
+        let texel =
+            |dx: f64, dy: f64| self.pixel(wrap(x0 + dx, self.width), wrap(y0 + dy, self.height));
+        (1.0 - tx) * (1.0 - ty) * texel(0.0, 0.0)
+            + tx * (1.0 - ty) * texel(1.0, 0.0)
+            + (1.0 - tx) * ty * texel(0.0, 1.0)
+            + tx * ty * texel(1.0, 1.0)
+    }
+}
+
+/// 逐个读取 PPM 文件头字段的辅助类型
+struct PpmReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl PpmReader<'_> {
+    /// 读取下一个字段，跳过空白和注释。
+    fn token(&mut self) -> io::Result<String> {
+        let data = self.data;
+        loop {
+            while self.pos < data.len() && data[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.pos < data.len() && data[self.pos] == b'#' {
+                while self.pos < data.len() && data[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+                continue;
+            }
+            break;
+        }
+        let start = self.pos;
+        while self.pos < data.len() && !data[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(invalid_data("PPM 数据不完整"));
+        }
+        Ok(String::from_utf8_lossy(&data[start..self.pos]).into_owned())
+    }
+
+    /// 读取下一个非负整数字段。
+    fn number(&mut self) -> io::Result<usize> {
+        self.token()?
+            .parse()
+            .map_err(|_| invalid_data("PPM 中的数字无效"))
+    }
+}
+
+/// 逐行、逐条扫描线读取 Radiance HDR 文件的辅助类型
+struct HdrReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl HdrReader<'_> {
+    /// 读取一个字节。
+    fn byte(&mut self) -> io::Result<u8> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| invalid_data("HDR 数据不完整"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// 读取文件头中的一行，不包括换行符。
+    fn line(&mut self) -> io::Result<String> {
+        let start = self.pos;
+        while self.byte()? != b'\n' {}
+        Ok(String::from_utf8_lossy(&self.data[start..self.pos - 1]).into_owned())
+    }
+
+    /// 读取一条宽度为 `width` 的扫描线，得到每个像素的 RGBE 值。
+    fn scanline(&mut self, width: usize) -> io::Result<Vec<[u8; 4]>> {
+        let header = self.data.get(self.pos..self.pos + 4);
+        let is_rle = (8..0x8000).contains(&width)
+            && header.is_some_and(|h| {
+                h[0] == 2 && h[1] == 2 && usize::from(h[2]) << 8 | usize::from(h[3]) == width
+            });
+        // 未压缩的扫描线每个像素占四个字节，数据不足时不必分配扫描线
+        if !is_rle && self.data.len() - self.pos < width.saturating_mul(4) {
+            return Err(invalid_data("HDR 数据不完整"));
+        }
+
+        let mut scanline = vec![[0; 4]; width];
+        if !is_rle {
+            for pixel in &mut scanline {
+                for c in pixel.iter_mut() {
+                    *c = self.byte()?;
+                }
+            }
+            return Ok(scanline);
+        }
+
+        // 新式游程编码：四个分量分别编码，计数大于 128 表示重复同一个值
+        self.pos += 4;
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = self.byte()? as usize;
+                let (count, run) = if count > 128 {
+                    (count - 128, true)
+                } else {
+                    (count, false)
+                };
+                if count == 0 || x + count > width {
+                    return Err(invalid_data("HDR 游程编码数据无效"));
+                }
+                let value = if run { self.byte()? } else { 0 };
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = if run { value } else { self.byte()? };
+                }
+                x += count;
+            }
+        }
+        Ok(scanline)
+    }
+}
+
+/// 检查图像的尺寸，返回像素数量；尺寸为零或像素数量溢出时返回错误。
+fn pixel_count(width: usize, height: usize) -> io::Result<usize> {
+    if width == 0 || height == 0 {
+        return Err(invalid_data("图像尺寸不能为零"));
+    }
+    width
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("图像尺寸过大"))
+}
+
+/// 创建一个表示数据格式错误的 IO 错误。
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,502 @@
+//! 积分器定义，负责计算一条射线带回的辐亮度，包含路径追踪以及若干简单的替代实现。
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::light::LightList;
+use crate::medium::MediumStack;
+use crate::ray::Ray;
+use crate::spectrum::{SampledSpectrum, SampledWavelengths};
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+
+/// 多重重要性采样（MIS）时使用的权重启发式。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MisHeuristic {
+    /// 平衡启发式：`f / (f + g)`
+    Balance,
+    /// 幂启发式（β = 2）：`f² / (f² + g²)`
+    #[default]
+    Power,
+}
+
+impl MisHeuristic {
+    /// 计算以概率密度 `pdf_f` 采样得到的样本的 MIS 权重，`pdf_g` 是另一种采样策略的概率密度。
+    #[inline]
+    pub fn weight(self, pdf_f: f64, pdf_g: f64) -> f64 {
+        let (f, g) = match self {
+            Self::Balance => (pdf_f, pdf_g),
+            Self::Power => (pdf_f * pdf_f, pdf_g * pdf_g),
+        };
+        if f + g > 0.0 { f / (f + g) } else { 0.0 }
+    }
+}
+
+/// 积分器接口
+pub trait Integrator {
+    /// 计算一条射线带回的辐亮度。
+    ///
+    /// # 参数
+    ///
+    /// * `r` - 要计算颜色的射线。
+    /// * `world` - 场景中的可命中对象。
+    /// * `lights` - 场景中可以被显式采样的光源。
+    ///
+    /// # 返回值
+    ///
+    /// 射线的颜色。
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color;
+
+    /// 每一轮采样开始前调用，第 `pass` 轮（从 0 开始）中每个像素都会采样一次。
+    ///
+    /// 可以用来生成光子图等与具体射线无关的数据，默认什么也不做。
+    fn begin_pass(&self, _pass: i32, _world: &dyn Hittable, _lights: &LightList) {}
+
+    /// 计算一条相机射线的颜色。
+    ///
+    /// 与 [`Integrator::ray_color`] 不同，这里可以访问生成射线的相机，并且可以向胶片上的任意像素
+    /// 累加贡献，例如从光源出发的路径直接连接到相机的情况。默认直接调用 `ray_color`。
+    ///
+    /// # 参数
+    ///
+    /// * `r` - 相机生成的射线。
+    /// * `camera` - 生成射线的相机。
+    /// * `film` - 渲染中的胶片，累加到上面的颜色与返回值使用相同的缩放。
+    /// * `world` - 场景中的可命中对象。
+    /// * `lights` - 场景中可以被显式采样的光源。
+    fn camera_ray_color(
+        &self,
+        r: Ray,
+        _camera: &Camera,
+        _film: &mut Film,
+        world: &dyn Hittable,
+        lights: &LightList,
+    ) -> Color {
+        self.ray_color(r, world, lights)
+    }
+}
+
+/// 场景中没有天空等无限远光源时，射线没有命中任何物体的背景颜色。
+pub fn background(r: Ray) -> Color {
+    // 这里实现一个从蓝色到白色的线性差值
+    let unit_direction = r.direction.unit_vector();
+    let a = 0.5 * (unit_direction.y + 1.0);
+    (1.0 - a) * Color::one() + a * Color::new(0.5, 0.7, 1.0)
+}
+
+/// 一次光源采样得到的直接光照，贡献为 `f * radiance * weight`
+pub(crate) struct DirectLight {
+    /// 碰撞点朝向光源方向的 `BSDF * cosθ`
+    pub f: Color,
+    /// 光源射向碰撞点的辐亮度，已经乘上了阴影光线沿途的透射率
+    pub radiance: Color,
+    /// MIS 权重除以光源采样的概率密度
+    pub weight: f64,
+}
+
+/// 对光源进行一次采样并进行阴影测试，得到直接光照的各个组成部分。
+///
+/// 参数与 [`sample_light`] 相同，没有贡献时返回 None。
+pub(crate) fn sample_direct_light<'a>(
+    r: Ray,
+    rec: &HitRecord<'a>,
+    world: &'a dyn Hittable,
+    lights: &LightList,
+    mis_heuristic: Option<MisHeuristic>,
+    media: &MediumStack<'a>,
+) -> Option<DirectLight> {
+    let sample = lights.sample(rec.p)?;
+
+    let shadow_ray = Ray::new(rec.p, sample.direction).with_wavelength(r.wavelength);
+    let f = rec.mat.eval(r, rec, shadow_ray);
+    if f.near_zero() || sample.radiance.near_zero() {
+        return None;
+    }
+
+    // 阴影光线离开碰撞点后所在的介质，光源被其他物体遮挡时没有贡献
+    let mut outgoing = media.clone();
+    outgoing.update(rec, shadow_ray);
+    let transmittance = outgoing.shadow_transmittance(world, shadow_ray, sample.distance)?;
+
+    let weight = match mis_heuristic {
+        Some(heuristic) if !sample.is_delta => {
+            let bsdf_pdf = rec.mat.scattering_pdf(r, rec, shadow_ray);
+            heuristic.weight(sample.pdf, bsdf_pdf)
+        }
+        _ => 1.0,
+    };
+    Some(DirectLight {
+        f,
+        radiance: sample.radiance * transmittance,
+        weight: weight / sample.pdf,
+    })
+}
+
+/// 对光源进行一次采样，计算碰撞点受到的直接光照。
+///
+/// # 参数
+///
+/// * `r` - 入射射线。
+/// * `rec` - 入射射线的碰撞记录。
+/// * `world` - 场景中的可命中对象，用于阴影测试。
+/// * `lights` - 场景中可以被显式采样的光源。
+/// * `mis_heuristic` - 与 BSDF 采样结合时使用的启发式，为 None 时只使用光源采样。
+/// * `media` - 入射射线到达碰撞点时所在的介质，阴影光线在穿过的介质中衰减。
+pub(crate) fn sample_light<'a>(
+    r: Ray,
+    rec: &HitRecord<'a>,
+    world: &'a dyn Hittable,
+    lights: &LightList,
+    mis_heuristic: Option<MisHeuristic>,
+    media: &MediumStack<'a>,
+) -> Color {
+    sample_direct_light(r, rec, world, lights, mis_heuristic, media)
+        .map_or(Color::zero(), |direct| {
+            direct.f * direct.radiance * direct.weight
+        })
+}
+
+/// 路径追踪积分器，结合 BSDF 采样与光源采样，是相机默认使用的积分器。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::hittable_list::HittableList;
+/// use ray_tracing_in_one_weekend::integrator::{Integrator, MisHeuristic, PathTracer};
+/// use ray_tracing_in_one_weekend::light::{AreaLight, LightList};
+/// use ray_tracing_in_one_weekend::material::{DiffuseLight, Lambertian};
+/// use ray_tracing_in_one_weekend::sphere::Sphere;
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// // 封闭的漫反射房间，里面有一个球形的灯
+/// let room = Rc::new(Lambertian::new(Color::new(0.3, 0.3, 0.3)));
+/// let lamp = Rc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+/// let bulb = || Sphere::new(Point3::new(0.0, 1.0, 0.0), 0.8, lamp.clone());
+/// let mut world = HittableList::new();
+/// world.add(Box::new(Sphere::new(Point3::zero(), 2.0, room)));
+/// world.add(Box::new(bulb()));
+/// let mut lights = LightList::new();
+/// lights.add(Box::new(AreaLight::new(Box::new(bulb()))));
+///
+/// // 看向地面的一个像素
+/// let r = Ray::new(Point3::new(0.0, -0.5, 0.0), Vec3::new(0.0, -1.0, 0.3));
+/// let mean = |integrator: &PathTracer, lights: &LightList, n: usize| {
+///     let sum: f64 = (0..n)
+///         .map(|_| integrator.ray_color(r, &world, lights).x)
+///         .sum();
+///     sum / n as f64
+/// };
+///
+/// // 反照率很低，最大深度截断的能量可以忽略，不同的采样策略都收敛到同一个值
+/// let reference = mean(&PathTracer::new(10), &lights, 4000);
+/// let balance = PathTracer {
+///     mis_heuristic: MisHeuristic::Balance,
+///     ..PathTracer::new(10)
+/// };
+/// let roulette = PathTracer {
+///     russian_roulette_depth: Some(1),
+///     ..PathTracer::new(10)
+/// };
+/// let estimates = [
+///     mean(&balance, &lights, 4000),
+///     mean(&roulette, &lights, 4000),
+///     // 没有可以采样的光源时只剩下 BSDF 采样
+///     mean(&PathTracer::new(10), &LightList::new(), 20000),
+/// ];
+/// for estimate in estimates {
+///     assert!((estimate - reference).abs() < 0.08 * reference, "{estimate} {reference}");
+/// }
+/// ```
+pub struct PathTracer {
+    /// 路径的最大散射次数
+    pub max_depth: i32,
+    /// 多重重要性采样使用的启发式
+    pub mis_heuristic: MisHeuristic,
+    /// 开始进行俄罗斯轮盘赌的散射次数，为 None 时不启用
+    pub russian_roulette_depth: Option<i32>,
+}
+
+impl PathTracer {
+    /// 创建一个新的路径追踪积分器实例。
+    pub fn new(max_depth: i32) -> Self {
+        Self {
+            max_depth,
+            mis_heuristic: MisHeuristic::default(),
+            russian_roulette_depth: None,
+        }
+    }
+}
+
+impl Integrator for PathTracer {
+    /// 使用循环代替递归逐段追踪路径，路径深度不会受到调用栈大小的限制。
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color {
+        let mut color = Color::zero();
+        // 路径吞吐量，即到目前为止所有散射衰减的乘积
+        let mut throughput = Color::one();
+        let mut ray = r;
+        // 上一次散射采样得到当前射线的概率密度，镜面散射或相机射线时为 None
+        let mut bsdf_pdf = None;
+        // 路径当前所在的介质
+        let mut media = MediumStack::new();
+
+        for depth in 0..self.max_depth {
+            // 光线在介质中传播到碰撞点的衰减
+            let (hit, transmittance) = media.hit(world, ray);
+            throughput = throughput * transmittance;
+            // 如果没有命中物体，那么加上背景颜色
+            let Some(rec) = hit else {
+                // 背景中的太阳等也可能由光源采样得到，同样按照 MIS 权重计入
+                let mut radiance = lights.background(ray);
+                if let Some(pdf) = bsdf_pdf {
+                    let light_pdf = lights.pdf(ray.origin, ray.direction);
+                    radiance *= self.mis_heuristic.weight(pdf, light_pdf);
+                }
+                color += throughput * radiance;
+                break;
+            };
+
+            // 物体自身发出的光，如果这个方向也可能由光源采样得到，那么按照 MIS 权重计入
+            let mut emitted = rec.mat.emitted(ray, &rec);
+            if let Some(pdf) = bsdf_pdf
+                && !emitted.near_zero()
+            {
+                let light_pdf = lights.pdf(ray.origin, ray.direction);
+                emitted *= self.mis_heuristic.weight(pdf, light_pdf);
+            }
+            color += throughput * emitted;
+
+            let Some(srec) = rec.mat.scatter(ray, &rec) else {
+                break;
+            };
+
+            // 镜面散射无法与光源采样结合，直接沿散射方向继续追踪
+            if srec.is_specular {
+                bsdf_pdf = None;
+            } else {
+                color += throughput
+                    * sample_light(ray, &rec, world, lights, Some(self.mis_heuristic), &media);
+                bsdf_pdf = Some(srec.pdf);
+            }
+            media.update(&rec, srec.scattered);
+            throughput = throughput * srec.attenuation;
+            ray = srec.scattered;
+
+            // 俄罗斯轮盘赌：吞吐量越低，路径越有可能被终止，存活的路径按存活概率补偿以保持无偏
+            if let Some(min_depth) = self.russian_roulette_depth
+                && depth + 1 >= min_depth
+            {
+                let survival = throughput.x.max(throughput.y).max(throughput.z).min(1.0);
+                if random_double() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+
+        color
+    }
+}
+
+/// 光谱路径追踪积分器
+///
+/// 与 [`PathTracer`] 相同地结合 BSDF 采样与光源采样，但每条相机射线同时追踪一组采样波长，
+/// 材质的反射率、光源和背景的颜色都先上采样为光谱，最后再经过 CIE XYZ 转换回 sRGB。
+/// 配合 [`Dielectric::with_dispersion`](crate::material::Dielectric::with_dispersion)
+/// 可以渲染出色散的效果。
+pub struct SpectralPathTracer {
+    /// 路径的最大散射次数
+    pub max_depth: i32,
+    /// 多重重要性采样使用的启发式
+    pub mis_heuristic: MisHeuristic,
+    /// 开始进行俄罗斯轮盘赌的散射次数，为 None 时不启用
+    pub russian_roulette_depth: Option<i32>,
+}
+
+impl SpectralPathTracer {
+    /// 创建一个新的光谱路径追踪积分器实例。
+    pub fn new(max_depth: i32) -> Self {
+        Self {
+            max_depth,
+            mis_heuristic: MisHeuristic::default(),
+            russian_roulette_depth: None,
+        }
+    }
+}
+
+impl Integrator for SpectralPathTracer {
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color {
+        let mut wavelengths = SampledWavelengths::sample();
+        let mut radiance = SampledSpectrum::constant(0.0);
+        let mut throughput = SampledSpectrum::constant(1.0);
+        // 射线只携带主波长，材质按照主波长计算与波长相关的散射
+        let mut ray = r.with_wavelength(Some(wavelengths.hero()));
+        let mut bsdf_pdf = None;
+        let mut media = MediumStack::new();
+
+        for depth in 0..self.max_depth {
+            let (hit, transmittance) = media.hit(world, ray);
+            throughput *= SampledSpectrum::from_rgb(transmittance, &wavelengths);
+            let Some(rec) = hit else {
+                let mut background = lights.background(ray);
+                if let Some(pdf) = bsdf_pdf {
+                    let light_pdf = lights.pdf(ray.origin, ray.direction);
+                    background *= self.mis_heuristic.weight(pdf, light_pdf);
+                }
+                radiance += throughput * SampledSpectrum::from_rgb(background, &wavelengths);
+                break;
+            };
+
+            let mut emitted = rec.mat.emitted(ray, &rec);
+            if let Some(pdf) = bsdf_pdf
+                && !emitted.near_zero()
+            {
+                let light_pdf = lights.pdf(ray.origin, ray.direction);
+                emitted *= self.mis_heuristic.weight(pdf, light_pdf);
+            }
+            radiance += throughput * SampledSpectrum::from_rgb(emitted, &wavelengths);
+
+            let Some(srec) = rec.mat.scatter(ray, &rec) else {
+                break;
+            };
+
+            if srec.is_specular {
+                bsdf_pdf = None;
+            } else {
+                if let Some(direct) =
+                    sample_direct_light(ray, &rec, world, lights, Some(self.mis_heuristic), &media)
+                {
+                    radiance += throughput
+                        * SampledSpectrum::from_rgb(direct.f, &wavelengths)
+                        * SampledSpectrum::from_rgb(direct.radiance, &wavelengths)
+                        * direct.weight;
+                }
+                bsdf_pdf = Some(srec.pdf);
+            }
+
+            // 散射方向与波长相关时，其余波长无法沿同一条路径继续传播
+            if rec.mat.is_dispersive() {
+                wavelengths.terminate_secondary();
+            }
+            media.update(&rec, srec.scattered);
+            throughput *= SampledSpectrum::from_rgb(srec.attenuation, &wavelengths);
+            ray = srec.scattered.with_wavelength(Some(wavelengths.hero()));
+
+            if let Some(min_depth) = self.russian_roulette_depth
+                && depth + 1 >= min_depth
+            {
+                let survival = throughput.max_value().min(1.0);
+                if random_double() >= survival {
+                    break;
+                }
+                throughput *= 1.0 / survival;
+            }
+        }
+
+        radiance.to_rgb(&wavelengths)
+    }
+}
+
+/// 环境光遮蔽积分器
+///
+/// 在碰撞点的法线半球内按余弦分布发射一条射线，未被遮挡时为白色，被遮挡时为黑色。
+pub struct AmbientOcclusion {
+    /// 遮挡测试的最大距离，超过该距离的物体不算作遮挡
+    pub max_distance: f64,
+}
+
+impl AmbientOcclusion {
+    /// 创建一个新的环境光遮蔽积分器实例。
+    pub fn new(max_distance: f64) -> Self {
+        Self { max_distance }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, _: &LightList) -> Color {
+        let Some(rec) = world.hit(r, Interval::new(0.001, f64::INFINITY)) else {
+            return Color::one();
+        };
+
+        let direction = rec.normal + Vec3::random_unit();
+        let direction = if direction.near_zero() {
+            rec.normal
+        } else {
+            direction.unit_vector()
+        };
+
+        let occluded = world
+            .hit(
+                Ray::new(rec.p, direction),
+                Interval::new(0.001, self.max_distance),
+            )
+            .is_some();
+        if occluded {
+            Color::zero()
+        } else {
+            Color::one()
+        }
+    }
+}
+
+/// 法线可视化积分器，把碰撞点法线的各个分量从 [-1, 1] 映射到 [0, 1] 作为颜色。
+pub struct NormalVisualizer;
+
+impl Integrator for NormalVisualizer {
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, _: &LightList) -> Color {
+        match world.hit(r, Interval::new(0.001, f64::INFINITY)) {
+            Some(rec) => 0.5 * (rec.normal + Color::one()),
+            None => Color::zero(),
+        }
+    }
+}
+
+/// Whitted 风格的光线追踪积分器
+///
+/// 漫反射等非镜面表面只计算光源的直接光照，镜面反射和折射沿散射方向继续追踪。
+pub struct Whitted {
+    /// 镜面散射的最大次数
+    pub max_depth: i32,
+}
+
+impl Whitted {
+    /// 创建一个新的 Whitted 积分器实例。
+    pub fn new(max_depth: i32) -> Self {
+        Self { max_depth }
+    }
+}
+
+impl Integrator for Whitted {
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color {
+        let mut color = Color::zero();
+        let mut throughput = Color::one();
+        let mut ray = r;
+        let mut media = MediumStack::new();
+
+        for _ in 0..self.max_depth {
+            let (hit, transmittance) = media.hit(world, ray);
+            throughput = throughput * transmittance;
+            let Some(rec) = hit else {
+                color += throughput * lights.background(ray);
+                break;
+            };
+
+            color += throughput * rec.mat.emitted(ray, &rec);
+
+            let Some(srec) = rec.mat.scatter(ray, &rec) else {
+                break;
+            };
+
+            if !srec.is_specular {
+                color += throughput * sample_light(ray, &rec, world, lights, None, &media);
+                break;
+            }
+            media.update(&rec, srec.scattered);
+            throughput = throughput * srec.attenuation;
+            ray = srec.scattered;
+        }
+
+        color
+    }
+}
@@ -1,15 +1,33 @@
 //! 项目跟模块，包含常用的模块和导出类型。
 
+pub mod aabb;
+pub mod alpha_mask;
+pub mod bdpt;
 pub mod camera;
 pub mod color;
+pub mod distribution;
+pub mod environment;
+pub mod film;
 pub mod hittable;
 pub mod hittable_list;
+pub mod ies;
+pub mod image;
+pub mod integrator;
 pub mod interval;
+pub mod light;
+pub mod light_bvh;
+pub mod material;
+pub mod medium;
+pub mod microfacet;
+pub mod normal_map;
+pub mod onb;
+pub mod photon_map;
 pub mod ray;
+pub mod sky;
+pub mod spectrum;
 pub mod sphere;
 pub mod utils;
 pub mod vec3;
-pub mod material;
 
 pub use color::Color;
 pub use ray::{Point3, Ray};
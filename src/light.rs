@@ -0,0 +1,773 @@
+//! 光源定义以及光源采样相关逻辑。
+
+use crate::aabb::Aabb;
+use crate::color::{Color, luminance};
+use crate::hittable::Hittable;
+use crate::integrator::background;
+use crate::interval::Interval;
+use crate::light_bvh::{LightBounds, LightBvh};
+use crate::onb::Onb;
+use crate::ray::{Point3, Ray};
+use crate::utils::{random_double, random_int};
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+/// 光源采样结果
+pub struct LightSample {
+    /// 从着色点指向光源的单位方向向量
+    pub direction: Vec3,
+    /// 着色点到光源采样点的距离，用于阴影测试
+    pub distance: f64,
+    /// 光源沿采样方向射向着色点的辐亮度
+    pub radiance: Color,
+    /// 采样方向对应的立体角概率密度
+    pub pdf: f64,
+    /// 光源采样点的表面法线，点光源等没有表面的光源为 None
+    pub normal: Option<Vec3>,
+    /// 是否来自 delta 分布的光源，此时 `pdf` 不是真正的概率密度，无法与 BSDF 采样结合
+    pub is_delta: bool,
+}
+
+/// 光源发射采样结果，用于从光源出发追踪路径
+pub struct EmissionSample {
+    /// 从光源发出的光线，方向为单位向量
+    pub ray: Ray,
+    /// 发射点的表面法线，点光源等没有表面的光源为 None
+    pub normal: Option<Vec3>,
+    /// 光源沿发射方向的辐亮度（点光源为辐射强度）
+    pub radiance: Color,
+    /// 发射点的面积概率密度，位置为 delta 分布的光源为 1
+    pub pdf_pos: f64,
+    /// 发射方向的立体角概率密度
+    pub pdf_dir: f64,
+}
+
+/// 可以被显式采样的光源
+pub trait Light {
+    /// 从着色点 `p` 出发，对光源进行一次采样。
+    ///
+    /// # 返回值
+    ///
+    /// 如果采样成功，返回采样结果；否则返回 None。
+    fn sample(&self, p: Point3) -> Option<LightSample>;
+
+    /// 从 `origin` 出发朝 `direction` 方向采样到该光源的立体角概率密度。
+    fn pdf(&self, origin: Point3, direction: Vec3) -> f64;
+
+    /// 光源是否为 delta 分布，即无法被光线随机命中（例如点光源）。
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// 从光源上随机发射一条光线。
+    ///
+    /// # 返回值
+    ///
+    /// 如果采样成功，返回发射采样结果；否则返回 None。
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        None
+    }
+
+    /// 光源上的点 `p` 沿 `direction` 方向发射光线时，发射点的面积概率密度和发射方向的立体角概率密度。
+    ///
+    /// `p` 不在光源上时返回 `(0.0, 0.0)`。
+    fn pdf_emission(&self, _p: Point3, _direction: Vec3) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+
+    /// 光源是否位于无限远处（例如天空），这样的光源通过 [`Light::background`] 照亮逃逸出场景的光线。
+    fn is_infinite(&self) -> bool {
+        false
+    }
+
+    /// 射线没有命中任何物体时，沿射线方向从该光源接收到的辐亮度。
+    fn background(&self, _r: Ray) -> Color {
+        Color::zero()
+    }
+
+    /// 光源的空间范围、功率和发光方向的界限，用于构建 [`LightBvh`]。
+    ///
+    /// 没有空间范围的光源（例如天空、平行光）返回 None。
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+}
+
+/// 面光源，由一个使用自发光材质的物体构成。
+///
+/// 物体本身仍然需要加入场景中，这里保存的是用于采样的副本。
+pub struct AreaLight {
+    pub shape: Box<dyn Hittable>,
+}
+
+impl AreaLight {
+    /// 检查点是否在光源表面上时，探测光线起点到该点的距离
+    const PROBE_DISTANCE: f64 = 1e-4;
+    /// 估计光源功率时在表面上采样的点数
+    const POWER_SAMPLES: usize = 16;
+
+    /// 创建一个新的面光源实例。
+    pub fn new(shape: Box<dyn Hittable>) -> Self {
+        Self { shape }
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let direction = self.shape.random(p).unit_vector();
+        let pdf = self.shape.pdf_value(p, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        let ray = Ray::new(p, direction);
+        let rec = self.shape.hit(ray, Interval::new(0.001, f64::INFINITY))?;
+
+        Some(LightSample {
+            direction,
+            distance: rec.t,
+            radiance: rec.mat.emitted(ray, &rec),
+            pdf,
+            normal: Some(rec.normal),
+            is_delta: false,
+        })
+    }
+
+    fn pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.shape.pdf_value(origin, direction)
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        let area = self.shape.area();
+        let rec = self.shape.sample_surface()?;
+        if area <= 0.0 {
+            return None;
+        }
+
+        // 在法线所在的半球内按余弦分布采样发射方向
+        let direction = rec.normal + Vec3::random_unit();
+        let direction = if direction.near_zero() {
+            rec.normal
+        } else {
+            direction.unit_vector()
+        };
+        let cos_theta = direction.dot(rec.normal);
+        if cos_theta <= 0.0 {
+            return None;
+        }
+
+        // 自发光材质按照“看向发射点的光线”计算辐亮度
+        let radiance = rec
+            .mat
+            .emitted(Ray::new(rec.p + direction, -direction), &rec);
+
+        Some(EmissionSample {
+            ray: Ray::new(rec.p, direction),
+            normal: Some(rec.normal),
+            radiance,
+            pdf_pos: 1.0 / area,
+            pdf_dir: cos_theta / PI,
+        })
+    }
+
+    fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        // 从发射方向一侧射向 p 点，检查 p 是否在光源表面上，同时得到该点朝向发射方向一侧的法线
+        let direction = direction.unit_vector();
+        let probe = Ray::new(p + Self::PROBE_DISTANCE * direction, -direction);
+        let Some(rec) = self
+            .shape
+            .hit(probe, Interval::new(0.0, 2.0 * Self::PROBE_DISTANCE))
+        else {
+            return (0.0, 0.0);
+        };
+
+        let area = self.shape.area();
+        if area <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (1.0 / area, direction.dot(rec.normal).max(0.0) / PI)
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = self.shape.bounding_box()?;
+
+        // 在表面上采样若干点估计平均辐亮度，漫反射发光表面的功率为 L * A * π。
+        // 带遮罩的表面会拒绝部分采样点，被拒绝的点按照不发光计入平均值
+        let mut radiance = 0.0;
+        let mut accepted = 0;
+        for _ in 0..Self::POWER_SAMPLES {
+            let Some(rec) = self.shape.sample_surface() else {
+                continue;
+            };
+            let ray = Ray::new(rec.p + rec.normal, -rec.normal);
+            radiance += luminance(rec.mat.emitted(ray, &rec));
+            accepted += 1;
+        }
+        if accepted == 0 {
+            return None;
+        }
+        let radiance = radiance / Self::POWER_SAMPLES as f64;
+
+        // 任意形状的法线都可能朝向任何方向
+        Some(LightBounds {
+            bounds,
+            phi: radiance * self.shape.area() * PI,
+            direction: Vec3::unit_z(),
+            cos_theta_o: -1.0,
+            cos_theta_e: 0.0,
+            two_sided: false,
+        })
+    }
+}
+
+/// 点光源，向所有方向均匀地发光，照度随距离的平方衰减。
+///
+/// 点光源无法被光线随机命中，只能通过光源采样和阴影测试照亮场景。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::light::{Light, PointLight};
+/// use ray_tracing_in_one_weekend::{Color, Point3};
+///
+/// let light = PointLight::new(Point3::new(0.0, 2.0, 0.0), Color::new(8.0, 8.0, 8.0));
+/// let near = light.sample(Point3::new(0.0, 0.0, 0.0)).unwrap();
+/// let far = light.sample(Point3::new(0.0, -2.0, 0.0)).unwrap();
+/// assert!((near.radiance.x - 2.0).abs() < 1e-12);
+/// // 距离加倍，照度变为四分之一
+/// assert!((far.radiance.x - near.radiance.x / 4.0).abs() < 1e-12);
+/// assert!(far.is_delta && light.pdf(Point3::zero(), near.direction) == 0.0);
+/// ```
+pub struct PointLight {
+    /// 光源位置
+    pub position: Point3,
+    /// 辐射强度
+    pub intensity: Color,
+}
+
+impl PointLight {
+    /// 创建一个新的点光源实例。
+    pub fn new(position: Point3, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let offset = self.position - p;
+        let distance_squared = offset.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+
+        Some(LightSample {
+            direction: offset / distance,
+            distance,
+            radiance: self.intensity / distance_squared,
+            pdf: 1.0,
+            normal: None,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _: Point3, _: Vec3) -> f64 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        Some(EmissionSample {
+            ray: Ray::new(self.position, Vec3::random_unit()),
+            normal: None,
+            radiance: self.intensity,
+            pdf_pos: 1.0,
+            pdf_dir: 1.0 / (4.0 * PI),
+        })
+    }
+
+    fn pdf_emission(&self, p: Point3, _: Vec3) -> (f64, f64) {
+        if (p - self.position).near_zero() {
+            (1.0, 1.0 / (4.0 * PI))
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        Some(LightBounds {
+            bounds: Aabb::point(self.position),
+            phi: 4.0 * PI * luminance(self.intensity),
+            direction: Vec3::unit_z(),
+            cos_theta_o: -1.0,
+            cos_theta_e: 0.0,
+            two_sided: false,
+        })
+    }
+}
+
+/// 聚光灯，在以 `direction` 为轴的圆锥内发光，圆锥边缘的光强平滑地衰减到零。
+///
+/// 与点光源一样，照度随距离的平方衰减，只能通过光源采样和阴影测试照亮场景。
+pub struct SpotLight {
+    /// 光源位置
+    pub position: Point3,
+    /// 圆锥轴线方向，单位向量
+    pub direction: Vec3,
+    /// 圆锥轴线上的辐射强度
+    pub intensity: Color,
+    /// 圆锥半角的余弦值，圆锥外没有光
+    pub cos_total_width: f64,
+    /// 开始衰减处半角的余弦值，在此之内光强不衰减
+    pub cos_falloff_start: f64,
+}
+
+impl SpotLight {
+    /// 创建一个新的聚光灯实例。
+    ///
+    /// # 参数
+    ///
+    /// - `position`：光源位置
+    /// - `target`：圆锥轴线指向的点
+    /// - `intensity`：圆锥轴线上的辐射强度
+    /// - `total_width`：圆锥的半角，单位为角度
+    /// - `falloff_start`：开始衰减处的半角，单位为角度，不超过 `total_width`
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::light::{Light, SpotLight};
+    /// use ray_tracing_in_one_weekend::{Color, Point3};
+    ///
+    /// let light = SpotLight::new(
+    ///     Point3::zero(),
+    ///     Point3::new(0.0, -1.0, 0.0),
+    ///     Color::new(1.0, 1.0, 1.0),
+    ///     30.0,
+    ///     20.0,
+    /// );
+    /// // 与轴线成 angle 度、距离为 1 的点
+    /// let at = |angle: f64| {
+    ///     let (sin, cos) = angle.to_radians().sin_cos();
+    ///     light.sample(Point3::new(sin, -cos, 0.0)).map(|sample| sample.radiance.x)
+    /// };
+    ///
+    /// // 衰减开始之前是完整的光强
+    /// assert!((at(0.0).unwrap() - 1.0).abs() < 1e-12);
+    /// assert!((at(15.0).unwrap() - 1.0).abs() < 1e-12);
+    /// // 两个圆锥之间按 smoothstep 衰减
+    /// let (cos_start, cos_end) = (20.0f64.to_radians().cos(), 30.0f64.to_radians().cos());
+    /// let t = (25.0f64.to_radians().cos() - cos_end) / (cos_start - cos_end);
+    /// assert!((at(25.0).unwrap() - t * t * (3.0 - 2.0 * t)).abs() < 1e-12);
+    /// // 圆锥之外没有光
+    /// assert!(at(35.0).is_none());
+    /// ```
+    pub fn new(
+        position: Point3,
+        target: Point3,
+        intensity: Color,
+        total_width: f64,
+        falloff_start: f64,
+    ) -> Self {
+        let falloff_start = falloff_start.min(total_width);
+        Self {
+            position,
+            direction: (target - position).unit_vector(),
+            intensity,
+            cos_total_width: total_width.to_radians().cos(),
+            cos_falloff_start: falloff_start.to_radians().cos(),
+        }
+    }
+
+    /// 沿单位向量 `w` 方向发出的辐射强度，在两个圆锥之间按 smoothstep 衰减。
+    fn intensity(&self, w: Vec3) -> Color {
+        let cos_theta = self.direction.dot(w);
+        if cos_theta < self.cos_total_width {
+            return Color::zero();
+        }
+        if cos_theta >= self.cos_falloff_start {
+            return self.intensity;
+        }
+        let t =
+            (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+        self.intensity * (t * t * (3.0 - 2.0 * t))
+    }
+
+    /// 在圆锥内均匀采样发射方向的立体角概率密度
+    fn cone_pdf(&self) -> f64 {
+        1.0 / (2.0 * PI * (1.0 - self.cos_total_width))
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let offset = self.position - p;
+        let distance_squared = offset.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+
+        let intensity = self.intensity(-direction);
+        if intensity.near_zero() {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: intensity / distance_squared,
+            pdf: 1.0,
+            normal: None,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _: Point3, _: Vec3) -> f64 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        // 在圆锥内均匀采样发射方向
+        let cos_theta = 1.0 - random_double() * (1.0 - self.cos_total_width);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random_double();
+        let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        let direction = Onb::new(self.direction).transform(local).unit_vector();
+
+        Some(EmissionSample {
+            ray: Ray::new(self.position, direction),
+            normal: None,
+            radiance: self.intensity(direction),
+            pdf_pos: 1.0,
+            pdf_dir: self.cone_pdf(),
+        })
+    }
+
+    fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        if !(p - self.position).near_zero() {
+            return (0.0, 0.0);
+        }
+        if self.direction.dot(direction.unit_vector()) < self.cos_total_width {
+            (1.0, 0.0)
+        } else {
+            (1.0, self.cone_pdf())
+        }
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        // 把点光源看作法线都在不衰减的圆锥内、每个方向再向外扩展到衰减结束处的发光表面
+        let theta_start = self.cos_falloff_start.clamp(-1.0, 1.0).acos();
+        let theta_end = self.cos_total_width.clamp(-1.0, 1.0).acos();
+        Some(LightBounds {
+            bounds: Aabb::point(self.position),
+            phi: 4.0 * PI * luminance(self.intensity),
+            direction: self.direction,
+            cos_theta_o: self.cos_falloff_start,
+            cos_theta_e: (theta_end - theta_start).cos(),
+            two_sided: false,
+        })
+    }
+}
+
+/// 平行光，模拟太阳这样距离极远的光源，所有光线都沿同一方向传播，照度不随距离衰减。
+///
+/// 从光源出发追踪路径时（双向路径追踪、光子映射），光线从场景包围球外一个垂直于光线方向的圆盘上发出，
+/// 因此需要通过 [`DirectionalLight::scene_bounds`] 指定能包住整个场景的球体。
+///
+/// # 示例
+/// ```
+/// use std::f64::consts::PI;
+/// use ray_tracing_in_one_weekend::light::{DirectionalLight, Light};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Vec3};
+///
+/// let center = Point3::new(1.0, 2.0, 3.0);
+/// let radius = 5.0;
+/// let light = DirectionalLight::new(Vec3::new(0.0, -1.0, 0.0), Color::new(3.0, 3.0, 3.0))
+///     .scene_bounds(center, radius);
+///
+/// for _ in 0..100 {
+///     let emission = light.sample_emission().unwrap();
+///     let origin = emission.ray.origin;
+///     // 发射点位于包围球上方、与光线方向垂直的圆盘上
+///     assert!((origin.y - (center.y + radius)).abs() < 1e-9);
+///     let radial = Vec3::new(origin.x - center.x, 0.0, origin.z - center.z);
+///     assert!(radial.length() <= radius + 1e-9);
+///     assert_eq!(emission.ray.direction, light.direction);
+///
+///     let (pdf_pos, pdf_dir) = light.pdf_emission(origin, emission.ray.direction);
+///     assert!((pdf_pos - 1.0 / (PI * radius * radius)).abs() < 1e-12);
+///     assert_eq!(pdf_dir, 1.0);
+/// }
+/// ```
+pub struct DirectionalLight {
+    /// 光线的传播方向，单位向量
+    pub direction: Vec3,
+    /// 垂直于光线方向的平面上的辐照度
+    pub irradiance: Color,
+    /// 场景包围球的球心
+    pub scene_center: Point3,
+    /// 场景包围球的半径
+    pub scene_radius: f64,
+}
+
+impl DirectionalLight {
+    /// 创建一个新的平行光实例，光线沿 `direction` 方向传播。
+    ///
+    /// 场景包围球默认以原点为球心、半径为 1000。
+    pub fn new(direction: Vec3, irradiance: Color) -> Self {
+        Self {
+            direction: direction.unit_vector(),
+            irradiance,
+            scene_center: Point3::zero(),
+            scene_radius: 1000.0,
+        }
+    }
+
+    /// 设置场景包围球，包围球越贴合场景，从光源发出的光线越不容易错过场景。
+    pub fn scene_bounds(mut self, center: Point3, radius: f64) -> Self {
+        self.scene_center = center;
+        self.scene_radius = radius;
+        self
+    }
+
+    /// 发射光线的圆盘圆心，位于包围球外逆着光线方向的一侧
+    fn disk_center(&self) -> Point3 {
+        self.scene_center - self.scene_radius * self.direction
+    }
+
+    /// 发射圆盘的面积概率密度
+    fn disk_pdf(&self) -> f64 {
+        1.0 / (PI * self.scene_radius * self.scene_radius)
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        // 阴影测试一直进行到发射圆盘所在的平面，使光源采样点与发射光线的起点一致
+        let distance = (p - self.disk_center()).dot(self.direction);
+        if distance <= 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction: -self.direction,
+            distance,
+            radiance: self.irradiance,
+            pdf: 1.0,
+            normal: None,
+            is_delta: true,
+        })
+    }
+
+    fn pdf(&self, _: Point3, _: Vec3) -> f64 {
+        0.0
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        if self.scene_radius <= 0.0 {
+            return None;
+        }
+
+        // 在垂直于光线方向的圆盘上均匀采样发射点
+        let disk = Vec3::random_in_unit_disk();
+        let onb = Onb::new(self.direction);
+        let origin = self.disk_center() + self.scene_radius * (disk.x * onb.u() + disk.y * onb.v());
+
+        Some(EmissionSample {
+            ray: Ray::new(origin, self.direction),
+            normal: None,
+            radiance: self.irradiance,
+            pdf_pos: self.disk_pdf(),
+            pdf_dir: 1.0,
+        })
+    }
+
+    fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        if self.scene_radius <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        // 检查 p 是否在发射圆盘上
+        let offset = p - self.disk_center();
+        let height = offset.dot(self.direction);
+        let radial = offset - height * self.direction;
+        let tolerance = 1e-6 * self.scene_radius;
+        if height.abs() > tolerance || radial.length() > self.scene_radius + tolerance {
+            return (0.0, 0.0);
+        }
+
+        let pdf_dir = if direction.unit_vector().dot(self.direction) > 1.0 - 1e-9 {
+            1.0
+        } else {
+            0.0
+        };
+        (self.disk_pdf(), pdf_dir)
+    }
+}
+
+/// 场景中所有可被采样的光源
+pub struct LightList {
+    /// 光源列表
+    pub lights: Vec<Box<dyn Light>>,
+    /// 通过 [`LightList::build_bvh`] 构建的光源 BVH
+    bvh: Option<LightBvh>,
+}
+
+impl LightList {
+    /// 创建一个新的空 LightList 实例。
+    pub fn new() -> Self {
+        Self {
+            lights: Vec::new(),
+            bvh: None,
+        }
+    }
+
+    /// 添加一个光源到列表中，已经构建的光源 BVH 会被丢弃。
+    pub fn add(&mut self, light: Box<dyn Light>) {
+        self.lights.push(light);
+        self.bvh = None;
+    }
+
+    /// 为列表中的光源构建光源 BVH。
+    ///
+    /// 构建之后 [`LightList::sample`] 按照光源对着色点的估计贡献选择光源，适合有大量小光源的场景；
+    /// 从光源出发的 [`LightList::choose`] 仍然均匀地选择。应当在添加完所有光源之后调用。
+    ///
+    /// # 示例
+    /// ```
+    /// use std::rc::Rc;
+    /// use ray_tracing_in_one_weekend::light::{AreaLight, LightList};
+    /// use ray_tracing_in_one_weekend::material::DiffuseLight;
+    /// use ray_tracing_in_one_weekend::sphere::Sphere;
+    /// use ray_tracing_in_one_weekend::{Color, Point3};
+    ///
+    /// // 着色点周围一圈互不遮挡的球形光源
+    /// let mut lights = LightList::new();
+    /// for i in 0..8 {
+    ///     let angle = i as f64 * std::f64::consts::FRAC_PI_4;
+    ///     let center = Point3::new(4.0 * angle.cos(), 1.0 + 0.5 * i as f64, 4.0 * angle.sin());
+    ///     let light = Rc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0)));
+    ///     lights.add(Box::new(AreaLight::new(Box::new(Sphere::new(center, 0.5, light)))));
+    /// }
+    /// lights.build_bvh();
+    ///
+    /// // 采样得到的概率密度与按方向计算的概率密度一致
+    /// let p = Point3::zero();
+    /// for _ in 0..100 {
+    ///     let sample = lights.sample(p).unwrap();
+    ///     let pdf = lights.pdf(p, sample.direction);
+    ///     assert!((sample.pdf - pdf).abs() <= 1e-9 * pdf);
+    /// }
+    /// ```
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(LightBvh::new(&self.lights));
+    }
+
+    /// 列表中是否没有任何光源
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// 均匀地随机选择一个光源。
+    ///
+    /// # 返回值
+    ///
+    /// 被选中的光源以及选中它的概率；列表为空时返回 None。
+    pub fn choose(&self) -> Option<(&dyn Light, f64)> {
+        if self.lights.is_empty() {
+            return None;
+        }
+
+        let index = random_int(0, self.lights.len() as i32 - 1) as usize;
+        Some((self.lights[index].as_ref(), 1.0 / self.lights.len() as f64))
+    }
+
+    /// 为着色点 `p` 选择一个光源并进行采样，返回的概率密度已经包含了选择光源的概率。
+    ///
+    /// 构建了光源 BVH 时按照光源的估计贡献选择，否则均匀地选择。
+    pub fn sample(&self, p: Point3) -> Option<LightSample> {
+        let (light, pmf) = match &self.bvh {
+            Some(bvh) => {
+                let (index, pmf) = bvh.sample(p)?;
+                (self.lights[index].as_ref(), pmf)
+            }
+            None => self.choose()?,
+        };
+        let mut sample = light.sample(p)?;
+        sample.pdf *= pmf;
+        Some(sample)
+    }
+
+    /// 从 `origin` 出发朝 `direction` 方向，通过 [`LightList::sample`] 采样到的概率密度。
+    pub fn pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        match &self.bvh {
+            Some(bvh) => bvh.pdf(&self.lights, origin, direction),
+            None => self.choose_pdf(origin, direction),
+        }
+    }
+
+    /// 从 `origin` 出发朝 `direction` 方向，通过 [`LightList::choose`] 均匀选择光源再对光源采样得到的概率密度。
+    pub fn choose_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.lights.is_empty() {
+            return 0.0;
+        }
+
+        let weight = 1.0 / self.lights.len() as f64;
+        self.lights
+            .iter()
+            .map(|light| weight * light.pdf(origin, direction))
+            .sum()
+    }
+
+    /// 射线没有命中任何物体时的背景颜色。
+    ///
+    /// 列表中有位于无限远处的光源时为这些光源的辐亮度之和，否则使用默认的渐变背景 [`background`]。
+    pub fn background(&self, r: Ray) -> Color {
+        let mut infinite = self
+            .lights
+            .iter()
+            .filter(|light| light.is_infinite())
+            .peekable();
+        if infinite.peek().is_none() {
+            return background(r);
+        }
+        infinite.fold(Color::zero(), |acc, light| acc + light.background(r))
+    }
+
+    /// 通过 [`LightList::choose`] 和 [`Light::sample_emission`] 从光源上的点 `p` 沿 `direction`
+    /// 方向发射光线的概率密度，其中面积概率密度已经包含了选择光源的概率。
+    pub fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        if self.lights.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let weight = 1.0 / self.lights.len() as f64;
+        self.lights
+            .iter()
+            .map(|light| light.pdf_emission(p, direction))
+            .filter(|(pdf_pos, _)| *pdf_pos > 0.0)
+            .fold((0.0, 0.0), |(pos, _), (pdf_pos, pdf_dir)| {
+                (pos + weight * pdf_pos, pdf_dir)
+            })
+    }
+}
+
+impl Default for LightList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,451 @@
+//! 光源层次结构（光源 BVH），用于在大量光源中按照对着色点的估计贡献选择光源。
+
+use crate::aabb::{Aabb, axis_of};
+use crate::interval::Interval;
+use crate::light::Light;
+use crate::ray::{Point3, Ray};
+use crate::utils::{random_double, random_int};
+use crate::vec3::Vec3;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// 构建光源 BVH 时，每个坐标轴上划分的桶数
+const SPLIT_BUCKETS: usize = 12;
+
+/// 估计贡献时距离平方的下限，避免着色点与点光源重合时除以零
+const MIN_DISTANCE_SQUARED: f64 = 1e-8;
+
+/// 一个或一组光源的空间范围、功率以及发光方向的界限
+#[derive(Debug, Copy, Clone)]
+pub struct LightBounds {
+    /// 光源所在空间的包围盒
+    pub bounds: Aabb,
+    /// 光源发出的总功率，按亮度计算
+    pub phi: f64,
+    /// 发光表面法线所在圆锥的轴线，单位向量
+    pub direction: Vec3,
+    /// 发光表面法线与轴线夹角上限的余弦值，为 -1 时法线可以朝向任意方向
+    pub cos_theta_o: f64,
+    /// 每个发光点的发光方向与其法线夹角上限的余弦值，漫反射发光表面为 0
+    pub cos_theta_e: f64,
+    /// 是否双面发光
+    pub two_sided: bool,
+}
+
+impl LightBounds {
+    /// 合并两组光源的界限。
+    pub fn union(self, other: Self) -> Self {
+        if self.phi <= 0.0 {
+            return other;
+        }
+        if other.phi <= 0.0 {
+            return self;
+        }
+
+        let (direction, cos_theta_o) = union_cone(
+            (self.direction, self.cos_theta_o),
+            (other.direction, other.cos_theta_o),
+        );
+        Self {
+            bounds: self.bounds.union(other.bounds),
+            phi: self.phi + other.phi,
+            direction,
+            cos_theta_o,
+            cos_theta_e: self.cos_theta_e.min(other.cos_theta_e),
+            two_sided: self.two_sided || other.two_sided,
+        }
+    }
+
+    /// 估计这组光源对点 `p` 的贡献，返回值只用于相对比较。
+    ///
+    /// 估计是保守的：只要组内有光源能照亮 `p`，返回值就大于零。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::aabb::Aabb;
+    /// use ray_tracing_in_one_weekend::light_bvh::LightBounds;
+    /// use ray_tracing_in_one_weekend::{Point3, Vec3};
+    ///
+    /// // 点光源的包围盒退化为一个点，着色点与光源重合时估计值仍然是有限的正数
+    /// let position = Point3::new(1.0, 2.0, 3.0);
+    /// let bounds = LightBounds {
+    ///     bounds: Aabb::point(position),
+    ///     phi: 1.0,
+    ///     direction: Vec3::unit_z(),
+    ///     cos_theta_o: -1.0,
+    ///     cos_theta_e: 0.0,
+    ///     two_sided: false,
+    /// };
+    /// let importance = bounds.importance(position);
+    /// assert!(importance.is_finite() && importance > 0.0);
+    /// ```
+    pub fn importance(&self, p: Point3) -> f64 {
+        let center = self.bounds.centroid();
+        let offset = p - center;
+        let distance_squared = offset.length_squared();
+        // 避免点离包围盒很近时估计值过大，点光源的包围盒没有大小，还需要一个正的下限
+        let clamped_distance_squared = distance_squared
+            .max(self.bounds.diagonal().length() / 2.0)
+            .max(MIN_DISTANCE_SQUARED);
+
+        // 从包围盒中心指向 p 的方向与轴线的夹角 θw
+        let wi = if offset.near_zero() {
+            self.direction
+        } else {
+            offset / distance_squared.sqrt()
+        };
+        let mut cos_theta_w = self.direction.dot(wi);
+        if self.two_sided {
+            cos_theta_w = cos_theta_w.abs();
+        }
+        let sin_theta_w = (1.0 - cos_theta_w * cos_theta_w).max(0.0).sqrt();
+
+        // 包围盒的外接球从 p 看去所张的半角 θb
+        let radius_squared = self.bounds.diagonal().length_squared() / 4.0;
+        let (sin_theta_b, cos_theta_b) = if distance_squared < radius_squared {
+            (0.0, -1.0)
+        } else {
+            let sin_squared = radius_squared / distance_squared;
+            (sin_squared.sqrt(), (1.0 - sin_squared).max(0.0).sqrt())
+        };
+
+        // 组内所有方向中与 p 所在方向最接近的夹角：max(0, θw - θo - θb)
+        let sin_theta_o = (1.0 - self.cos_theta_o * self.cos_theta_o).max(0.0).sqrt();
+        let cos_theta_x = cos_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let sin_theta_x = sin_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, self.cos_theta_o);
+        let cos_theta_p = cos_sub_clamped(sin_theta_x, cos_theta_x, sin_theta_b, cos_theta_b);
+        if cos_theta_p <= self.cos_theta_e {
+            return 0.0;
+        }
+
+        (self.phi * cos_theta_p / clamped_distance_squared).max(0.0)
+    }
+
+    /// 构建时划分光源使用的代价：功率、发光方向范围和包围盒表面积的乘积。
+    fn cost(&self, node_bounds: &Aabb, axis: usize) -> f64 {
+        let theta_o = self.cos_theta_o.clamp(-1.0, 1.0).acos();
+        let theta_e = self.cos_theta_e.clamp(-1.0, 1.0).acos();
+        let theta_w = (theta_o + theta_e).min(PI);
+        let sin_theta_o = theta_o.sin();
+        // 发光方向覆盖的立体角的度量
+        let m_omega = 2.0 * PI * (1.0 - self.cos_theta_o)
+            + FRAC_PI_2
+                * (2.0 * theta_w * sin_theta_o
+                    - (theta_o - 2.0 * theta_w).cos()
+                    - 2.0 * theta_o * sin_theta_o
+                    + self.cos_theta_o);
+
+        // 惩罚沿着较短的坐标轴划分出的细长节点
+        let diagonal = node_bounds.diagonal();
+        let kr = diagonal.x.max(diagonal.y).max(diagonal.z) / axis_of(diagonal, axis);
+        self.phi * m_omega * kr * self.bounds.surface_area()
+    }
+}
+
+/// cos(max(0, a - b))，参数为两个角的正弦和余弦值
+#[inline]
+fn cos_sub_clamped(sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64) -> f64 {
+    if cos_a > cos_b {
+        1.0
+    } else {
+        cos_a * cos_b + sin_a * sin_b
+    }
+}
+
+/// sin(max(0, a - b))，参数为两个角的正弦和余弦值
+#[inline]
+fn sin_sub_clamped(sin_a: f64, cos_a: f64, sin_b: f64, cos_b: f64) -> f64 {
+    if cos_a > cos_b {
+        0.0
+    } else {
+        sin_a * cos_b - cos_a * sin_b
+    }
+}
+
+/// 同时包含两个方向圆锥的最小圆锥，圆锥由单位轴线和半角的余弦值表示。
+fn union_cone(a: (Vec3, f64), b: (Vec3, f64)) -> (Vec3, f64) {
+    let (a_dir, a_cos) = a;
+    let (b_dir, b_cos) = b;
+    let theta_a = a_cos.clamp(-1.0, 1.0).acos();
+    let theta_b = b_cos.clamp(-1.0, 1.0).acos();
+    let theta_d = a_dir.dot(b_dir).clamp(-1.0, 1.0).acos();
+
+    // 一个圆锥已经包含了另一个
+    if (theta_d + theta_b).min(PI) <= theta_a {
+        return a;
+    }
+    if (theta_d + theta_a).min(PI) <= theta_b {
+        return b;
+    }
+
+    let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+    if theta_o >= PI {
+        return (a_dir, -1.0);
+    }
+
+    // 把 a 的轴线绕两条轴线的公垂线旋转到新圆锥的轴线
+    let axis = Vec3::cross(a_dir, b_dir);
+    if axis.near_zero() {
+        return (a_dir, -1.0);
+    }
+    let axis = axis.unit_vector();
+    let theta_r = theta_o - theta_a;
+    let (sin_r, cos_r) = theta_r.sin_cos();
+    let direction =
+        a_dir * cos_r + Vec3::cross(axis, a_dir) * sin_r + axis * axis.dot(a_dir) * (1.0 - cos_r);
+    (direction.unit_vector(), theta_o.cos())
+}
+
+/// 光源 BVH 的节点
+struct Node {
+    /// 节点内所有光源的界限
+    bounds: LightBounds,
+    /// 叶子节点保存光源的序号，内部节点的第一个子节点紧跟在自身之后
+    content: NodeContent,
+}
+
+enum NodeContent {
+    /// 光源在光源列表中的序号
+    Leaf(usize),
+    /// 第二个子节点在节点数组中的序号
+    Interior { second: usize },
+}
+
+/// 光源 BVH
+///
+/// 按照光源的位置、功率和发光方向把光源逐层聚类，选择光源时从根节点出发，
+/// 按照两个子节点对着色点的估计贡献随机选择一个，直到叶子节点，
+/// 因此大量光源中只有离着色点近、功率大、朝向着色点的光源容易被选中。
+///
+/// 天空、平行光等没有空间范围的光源不放进树中，与整棵树一起均匀地选择。
+pub struct LightBvh {
+    /// 按深度优先顺序排列的节点
+    nodes: Vec<Node>,
+    /// 没有空间范围的光源在光源列表中的序号
+    infinite: Vec<usize>,
+}
+
+impl LightBvh {
+    /// 为光源列表构建光源 BVH，功率为零的光源不会被选中。
+    pub fn new(lights: &[Box<dyn Light>]) -> Self {
+        let mut infinite = Vec::new();
+        let mut bounded = Vec::new();
+        for (index, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bounds) if bounds.phi > 0.0 => bounded.push((index, bounds)),
+                Some(_) => {}
+                None => infinite.push(index),
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(2 * bounded.len());
+        if !bounded.is_empty() {
+            Self::build(&mut bounded, &mut nodes);
+        }
+        Self { nodes, infinite }
+    }
+
+    /// 递归地构建以 `lights` 为内容的子树，返回子树根节点的界限。
+    fn build(lights: &mut [(usize, LightBounds)], nodes: &mut Vec<Node>) -> LightBounds {
+        if let [(index, bounds)] = lights {
+            nodes.push(Node {
+                bounds: *bounds,
+                content: NodeContent::Leaf(*index),
+            });
+            return *bounds;
+        }
+
+        let mid = Self::split(lights);
+        let node = nodes.len();
+        nodes.push(Node {
+            bounds: lights[0].1,
+            content: NodeContent::Interior { second: 0 },
+        });
+        let first = Self::build(&mut lights[..mid], nodes);
+        let second = nodes.len();
+        let bounds = first.union(Self::build(&mut lights[mid..], nodes));
+        nodes[node] = Node {
+            bounds,
+            content: NodeContent::Interior { second },
+        };
+        bounds
+    }
+
+    /// 按照代价最小的方式把光源分成两组，返回第二组在重新排列后的 `lights` 中的起始位置。
+    fn split(lights: &mut [(usize, LightBounds)]) -> usize {
+        let node_bounds = lights
+            .iter()
+            .map(|(_, b)| b.bounds)
+            .reduce(Aabb::union)
+            .expect("light list must not be empty");
+        let centroid_bounds = lights
+            .iter()
+            .map(|(_, b)| Aabb::point(b.bounds.centroid()))
+            .reduce(Aabb::union)
+            .expect("light list must not be empty");
+
+        // 在每个坐标轴上把光源按中心位置分桶，寻找代价最小的划分
+        let mut best: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            let min = axis_of(centroid_bounds.min, axis);
+            let extent = axis_of(centroid_bounds.max, axis) - min;
+            if extent <= 0.0 || axis_of(node_bounds.diagonal(), axis) <= 0.0 {
+                continue;
+            }
+            let bucket_of = |b: &LightBounds| {
+                let t = (axis_of(b.bounds.centroid(), axis) - min) / extent;
+                ((t * SPLIT_BUCKETS as f64) as usize).min(SPLIT_BUCKETS - 1)
+            };
+
+            let mut buckets: [Option<LightBounds>; SPLIT_BUCKETS] = [None; SPLIT_BUCKETS];
+            for (_, b) in lights.iter() {
+                let bucket = &mut buckets[bucket_of(b)];
+                *bucket = Some(bucket.map_or(*b, |acc| acc.union(*b)));
+            }
+
+            for split in 0..SPLIT_BUCKETS - 1 {
+                let union = |range: &[Option<LightBounds>]| {
+                    range.iter().flatten().copied().reduce(LightBounds::union)
+                };
+                let (Some(below), Some(above)) =
+                    (union(&buckets[..=split]), union(&buckets[split + 1..]))
+                else {
+                    continue;
+                };
+                let cost = below.cost(&node_bounds, axis) + above.cost(&node_bounds, axis);
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+
+        // 所有光源的中心重合时无法按位置划分，直接对半分开
+        let Some((_, axis, split)) = best else {
+            return lights.len() / 2;
+        };
+        lights.sort_by(|(_, a), (_, b)| {
+            axis_of(a.bounds.centroid(), axis).total_cmp(&axis_of(b.bounds.centroid(), axis))
+        });
+        let min = axis_of(centroid_bounds.min, axis);
+        let extent = axis_of(centroid_bounds.max, axis) - min;
+        let mid = lights.partition_point(|(_, b)| {
+            let t = (axis_of(b.bounds.centroid(), axis) - min) / extent;
+            ((t * SPLIT_BUCKETS as f64) as usize).min(SPLIT_BUCKETS - 1) <= split
+        });
+        if mid == 0 || mid == lights.len() {
+            lights.len() / 2
+        } else {
+            mid
+        }
+    }
+
+    /// 选中没有空间范围的光源（而不是整棵树）的概率
+    fn infinite_probability(&self) -> f64 {
+        let n = self.infinite.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let trees = if self.nodes.is_empty() { 0 } else { 1 };
+        n as f64 / (n + trees) as f64
+    }
+
+    /// 为着色点 `p` 随机选择一个光源。
+    ///
+    /// # 返回值
+    ///
+    /// 被选中的光源在光源列表中的序号以及选中它的概率；没有光源能照亮 `p` 时返回 None。
+    pub fn sample(&self, p: Point3) -> Option<(usize, f64)> {
+        let p_infinite = self.infinite_probability();
+        if p_infinite > 0.0 && random_double() < p_infinite {
+            let n = self.infinite.len();
+            let index = random_int(0, n as i32 - 1) as usize;
+            return Some((self.infinite[index], p_infinite / n as f64));
+        }
+
+        let mut node = 0;
+        let mut pmf = 1.0 - p_infinite;
+        loop {
+            let current = self.nodes.get(node)?;
+            match current.content {
+                NodeContent::Leaf(light) => {
+                    return (current.bounds.importance(p) > 0.0).then_some((light, pmf));
+                }
+                NodeContent::Interior { second } => {
+                    let first_importance = self.nodes[node + 1].bounds.importance(p);
+                    let second_importance = self.nodes[second].bounds.importance(p);
+                    let total = first_importance + second_importance;
+                    if total <= 0.0 {
+                        return None;
+                    }
+                    let p_first = first_importance / total;
+                    if random_double() < p_first {
+                        node += 1;
+                        pmf *= p_first;
+                    } else {
+                        node = second;
+                        pmf *= 1.0 - p_first;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 从 `origin` 出发朝 `direction` 方向，通过 [`LightBvh::sample`] 选择光源再对光源采样得到的概率密度。
+    ///
+    /// `lights` 必须是构建时使用的光源列表。只有光线经过的节点才需要访问。
+    pub fn pdf(&self, lights: &[Box<dyn Light>], origin: Point3, direction: Vec3) -> f64 {
+        let p_infinite = self.infinite_probability();
+        let mut pdf = 0.0;
+        if !self.infinite.is_empty() {
+            let weight = p_infinite / self.infinite.len() as f64;
+            pdf += self
+                .infinite
+                .iter()
+                .map(|&index| weight * lights[index].pdf(origin, direction))
+                .sum::<f64>();
+        }
+        if !self.nodes.is_empty() {
+            let ray = Ray::new(origin, direction);
+            pdf += self.node_pdf(lights, 0, 1.0 - p_infinite, ray);
+        }
+        pdf
+    }
+
+    /// 子树 `node` 中的光源沿 `ray` 方向的概率密度之和，`pmf` 为选中该子树的概率。
+    fn node_pdf(&self, lights: &[Box<dyn Light>], node: usize, pmf: f64, ray: Ray) -> f64 {
+        let current = &self.nodes[node];
+        match current.content {
+            NodeContent::Leaf(light) => {
+                // 只有根节点是叶子时，选择前需要单独检查它能否照亮 p
+                if node == 0 && current.bounds.importance(ray.origin) <= 0.0 {
+                    return 0.0;
+                }
+                pmf * lights[light].pdf(ray.origin, ray.direction)
+            }
+            NodeContent::Interior { second } => {
+                let children = [node + 1, second];
+                let importance =
+                    children.map(|child| self.nodes[child].bounds.importance(ray.origin));
+                let total = importance[0] + importance[1];
+                if total <= 0.0 {
+                    return 0.0;
+                }
+
+                // 光线没有穿过子节点的包围盒时，其中的光源在该方向上的概率密度都为零
+                children
+                    .iter()
+                    .zip(importance)
+                    .filter(|&(&child, importance)| {
+                        importance > 0.0
+                            && self.nodes[child]
+                                .bounds
+                                .bounds
+                                .hit(ray, Interval::new(0.0, f64::INFINITY))
+                    })
+                    .map(|(&child, importance)| {
+                        self.node_pdf(lights, child, pmf * importance / total, ray)
+                    })
+                    .sum()
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use rand::distr::Distribution;
 use rand::distr::weighted::WeightedIndex;
 use ray_tracing_in_one_weekend::camera::CameraBuilder;
 use ray_tracing_in_one_weekend::hittable_list::HittableList;
+use ray_tracing_in_one_weekend::light::LightList;
 use ray_tracing_in_one_weekend::material::{Dielectric, Lambertian, Material, Metal};
 use ray_tracing_in_one_weekend::ray::Point3;
 use ray_tracing_in_one_weekend::sphere::Sphere;
@@ -54,7 +55,7 @@ fn main() {
         .defocus_angle(0.06)
         .focus_dist(10.0)
         .build();
-    camera.render(&world);
+    camera.render(&world, &LightList::new());
 }
 
 /// 生成一些随机的球体
@@ -77,7 +78,7 @@ fn generate_random_balls(world: &mut HittableList) {
     ];
 
     let weights = [85, 15, 5];
-    let dist = WeightedIndex::new(&weights).unwrap();
+    let dist = WeightedIndex::new(weights).unwrap();
     let mut rng = rand::rng();
 
     for a in -11..11 {
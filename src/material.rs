@@ -1,8 +1,52 @@
 //! 材质定义以及相关工具方法。
 
+use crate::color::luminance;
 use crate::hittable::HitRecord;
-use crate::{Color, Ray, Vec3};
+use crate::integrator::MisHeuristic;
+use crate::interval::Interval;
+use crate::medium::Medium;
+use crate::microfacet::{TrowbridgeReitz, fresnel_conductor};
+use crate::normal_map::NormalMap;
+use crate::onb::Onb;
+use crate::spectrum::Dispersion;
 use crate::utils::random_double;
+use crate::{Color, Point3, Ray, Vec3};
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+/// 散射记录，描述一次散射采样的结果。
+pub struct ScatterRecord {
+    /// 衰减，即采样权重 `BSDF * cosθ / pdf`
+    pub attenuation: Color,
+    /// 散射后的光线
+    pub scattered: Ray,
+    /// 散射方向对应的立体角概率密度，镜面散射时没有意义
+    pub pdf: f64,
+    /// 是否为镜面散射（delta 分布），镜面散射无法与光源采样结合
+    pub is_specular: bool,
+}
+
+impl ScatterRecord {
+    /// 创建一个非镜面散射的记录。
+    pub fn new(attenuation: Color, scattered: Ray, pdf: f64) -> Self {
+        Self {
+            attenuation,
+            scattered,
+            pdf,
+            is_specular: false,
+        }
+    }
+
+    /// 创建一个镜面散射的记录。
+    pub fn specular(attenuation: Color, scattered: Ray) -> Self {
+        Self {
+            attenuation,
+            scattered,
+            pdf: 0.0,
+            is_specular: true,
+        }
+    }
+}
 
 /// 材质定义
 pub trait Material {
@@ -15,8 +59,69 @@ pub trait Material {
     ///
     /// # 返回值
     ///
-    /// 如果散射成功，返回散射后的颜色和光线；否则返回 None。
-    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<(Color, Ray)>;
+    /// 如果散射成功，返回散射记录；否则返回 None。
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord>;
+
+    /// 计算给定散射方向上的 `BSDF * cosθ`，用于光源采样。
+    ///
+    /// 镜面材质无法被光源采样命中，默认返回黑色。
+    fn eval(&self, _r_in: Ray, _rec: &HitRecord<'_>, _scattered: Ray) -> Color {
+        Color::zero()
+    }
+
+    /// 计算 [`Material::scatter`] 采样到给定散射方向的立体角概率密度。
+    fn scattering_pdf(&self, _r_in: Ray, _rec: &HitRecord<'_>, _scattered: Ray) -> f64 {
+        0.0
+    }
+
+    /// 材质自身发出的光，默认不发光。
+    fn emitted(&self, _r_in: Ray, _rec: &HitRecord<'_>) -> Color {
+        Color::zero()
+    }
+
+    /// 散射方向或散射权重是否与入射光线的波长有关（例如色散、薄膜干涉）。
+    ///
+    /// 光谱渲染时，经过这类材质散射后只有主波长能够继续沿路径传播。
+    fn is_dispersive(&self) -> bool {
+        false
+    }
+
+    /// 材质所在物体内部的介质，光线进入物体后会在其中衰减，默认没有内部介质。
+    fn medium(&self) -> Option<Medium> {
+        None
+    }
+}
+
+/// 可以在表面覆盖 [`ThinFilm`] 涂层的材质
+///
+/// 涂层改变的是表面的反射率，散射方向的分布仍然由材质自身决定。
+pub trait Coatable: Material {
+    /// 涂层下方基底的折射率（分别对应 r、g、b 三个通道）。
+    fn substrate_ior(&self, r_in: Ray, rec: &HitRecord<'_>) -> Color;
+
+    /// 涂层下方基底复折射率的虚部，即消光系数，默认为 0（电介质基底）。
+    fn substrate_extinction(&self, _r_in: Ray, _rec: &HitRecord<'_>) -> Color {
+        Color::zero()
+    }
+
+    /// 用给定的反射率代替材质自身的反射率（菲涅尔项）进行散射。
+    fn scatter_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        reflectance: Color,
+    ) -> Option<ScatterRecord>;
+
+    /// 用给定的反射率代替材质自身的反射率计算 `BSDF * cosθ`，镜面材质默认返回黑色。
+    fn eval_with_reflectance(
+        &self,
+        _r_in: Ray,
+        _rec: &HitRecord<'_>,
+        _scattered: Ray,
+        _reflectance: Color,
+    ) -> Color {
+        Color::zero()
+    }
 }
 
 /// 朗伯材质
@@ -34,7 +139,164 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: Ray, rec: &HitRecord<'_>) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        // 法线加上单位球面上的随机向量，得到的方向服从余弦分布
+        let scatter_direction = rec.normal + Vec3::random_unit();
+        let scatter_direction = if scatter_direction.near_zero() {
+            rec.normal
+        } else {
+            scatter_direction
+        };
+
+        let scattered = Ray::new(rec.p, scatter_direction);
+        let pdf = self.scattering_pdf(r_in, rec, scattered);
+        Some(ScatterRecord::new(self.albedo, scattered, pdf))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        self.albedo * self.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, _: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        let cos_theta = rec.normal.dot(scattered.direction.unit_vector());
+        if cos_theta > 0.0 { cos_theta / PI } else { 0.0 }
+    }
+}
+
+/// Oren–Nayar 粗糙漫反射材质
+///
+/// 把表面看作由许多朝向随机的朗伯微表面组成，微表面之间的遮挡与相互照射使得
+/// 粗糙表面在逆光方向更暗、顺光方向更亮，适合混凝土、黏土和布料等表面。
+/// 粗糙度为 0 时退化为朗伯材质。
+///
+/// 经典模型在粗糙度较大时会丢失微表面之间多次散射的能量，表面整体偏暗。
+/// 能量守恒的变体（EON，Portsmouth 等，2024）使用 Fujii 改进的单次散射项，
+/// 并补回多次散射的能量，反照率为 1 的表面在任何粗糙度和角度下都不会损失能量。
+///
+/// 散射方向按照余弦分布采样。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Lambertian, Material, OrenNayar};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// let albedo = Color::new(0.8, 0.5, 0.2);
+/// let r_in = Ray::new(Point3::new(-2.0, 1.0, 0.0), Vec3::new(2.0, -1.0, 0.0));
+/// let lambertian = Lambertian::new(albedo);
+/// let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &lambertian);
+///
+/// // 粗糙度为 0 时两种模型都与朗伯材质相同
+/// for smooth in [OrenNayar::new(albedo, 0.0), OrenNayar::energy_preserving(albedo, 0.0)] {
+///     for _ in 0..100 {
+///         let scattered = Ray::new(Point3::zero(), Vec3::random_unit());
+///         let expected = lambertian.eval(r_in, &rec, scattered);
+///         assert!((smooth.eval(r_in, &rec, scattered) - expected).length() < 1e-12);
+///         let pdf = lambertian.scattering_pdf(r_in, &rec, scattered);
+///         assert_eq!(smooth.scattering_pdf(r_in, &rec, scattered), pdf);
+///     }
+/// }
+///
+/// // 反照率为 1 时，经典模型会损失能量，能量守恒的变体则把入射的能量全部反射出去
+/// let albedo_of = |material: &OrenNayar| {
+///     let n = 20000;
+///     let mut sum = 0.0;
+///     for _ in 0..n {
+///         let srec = material.scatter(r_in, &rec).unwrap();
+///         let pdf = material.scattering_pdf(r_in, &rec, srec.scattered);
+///         assert!((srec.pdf - pdf).abs() <= 1e-12 * pdf);
+///         sum += material.eval(r_in, &rec, srec.scattered).x / pdf;
+///     }
+///     sum / n as f64
+/// };
+/// assert!(albedo_of(&OrenNayar::new(Color::one(), 1.0)) < 0.9);
+/// assert!((albedo_of(&OrenNayar::energy_preserving(Color::one(), 1.0)) - 1.0).abs() < 0.01);
+/// ```
+pub struct OrenNayar {
+    pub albedo: Color,
+    /// 粗糙度，范围为 [0, 1]，经典模型中即微表面倾角的标准差（弧度）
+    pub roughness: f64,
+    /// 是否使用能量守恒的变体
+    pub energy_preserving: bool,
+}
+
+impl OrenNayar {
+    /// Fujii 模型中的常数 `1/2 - 2/(3π)`
+    const FUJII_C1: f64 = 0.5 - 2.0 / (3.0 * PI);
+    /// Fujii 模型中的常数 `2/3 - 28/(15π)`
+    const FUJII_C2: f64 = 2.0 / 3.0 - 28.0 / (15.0 * PI);
+
+    /// 创建一个新的经典 Oren–Nayar 材质实例。
+    pub fn new(albedo: Color, roughness: f64) -> Self {
+        Self {
+            albedo,
+            roughness: roughness.clamp(0.0, 1.0),
+            energy_preserving: false,
+        }
+    }
+
+    /// 创建一个新的能量守恒 Oren–Nayar 材质实例。
+    pub fn energy_preserving(albedo: Color, roughness: f64) -> Self {
+        Self {
+            energy_preserving: true,
+            ..Self::new(albedo, roughness)
+        }
+    }
+
+    /// 计算出射方向 `wo` 与入射方向 `wi` 之间的 BRDF。
+    fn brdf(&self, normal: Vec3, wo: Vec3, wi: Vec3) -> Color {
+        let cos_o = normal.dot(wo).clamp(0.0, 1.0);
+        let cos_i = normal.dot(wi).clamp(0.0, 1.0);
+        // s = sinθi sinθo cos(φi - φo)
+        let s = wo.dot(wi) - cos_o * cos_i;
+        let sigma = self.roughness;
+
+        if !self.energy_preserving {
+            let sigma2 = sigma * sigma;
+            let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+            let b = 0.45 * sigma2 / (sigma2 + 0.09);
+            // max(0, cos(φi - φo)) sinα tanβ，其中 α、β 分别为较大和较小的天顶角
+            let t = cos_o.max(cos_i).max(1e-7);
+            return self.albedo / PI * (a + b * s.max(0.0) / t);
+        }
+
+        let a = 1.0 / (1.0 + Self::FUJII_C1 * sigma);
+        let s_over_t = if s > 0.0 {
+            s / cos_o.max(cos_i).max(1e-7)
+        } else {
+            s
+        };
+        let single = self.albedo / PI * (a * (1.0 + sigma * s_over_t));
+
+        // 单次散射损失的能量按照两个方向上的方向反照率分配给多次散射项
+        let e_avg = a * (1.0 + Self::FUJII_C2 * sigma);
+        if 1.0 - e_avg < 1e-7 {
+            return single;
+        }
+        let channel = |rho: f64| rho * rho * e_avg / (1.0 - rho * (1.0 - e_avg));
+        let rho_ms = Color::new(
+            channel(self.albedo.x),
+            channel(self.albedo.y),
+            channel(self.albedo.z),
+        );
+        let lost = (1.0 - self.fujii_albedo(cos_o)) * (1.0 - self.fujii_albedo(cos_i));
+        single + rho_ms / PI * (lost / (1.0 - e_avg))
+    }
+
+    /// 反照率为 1 时 Fujii 单次散射项在方向 `cos_theta` 上的方向反照率。
+    fn fujii_albedo(&self, cos_theta: f64) -> f64 {
+        let a = 1.0 / (1.0 + Self::FUJII_C1 * self.roughness);
+        let b = self.roughness * a;
+        let mu = cos_theta.clamp(1e-7, 1.0);
+        let sin = (1.0 - mu * mu).sqrt();
+        let g =
+            sin * (mu.acos() - sin * mu) + 2.0 / 3.0 * ((sin / mu) * (1.0 - sin * sin * sin) - sin);
+        a + b / PI * g
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
         let scatter_direction = rec.normal + Vec3::random_unit();
         let scatter_direction = if scatter_direction.near_zero() {
             rec.normal
@@ -43,7 +305,27 @@ impl Material for Lambertian {
         };
 
         let scattered = Ray::new(rec.p, scatter_direction);
-        Some((self.albedo, scattered))
+        let pdf = self.scattering_pdf(r_in, rec, scattered);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let attenuation = self.eval(r_in, rec, scattered) / pdf;
+        Some(ScatterRecord::new(attenuation, scattered, pdf))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        let wo = -r_in.direction.unit_vector();
+        let wi = scattered.direction.unit_vector();
+        let cos_theta = rec.normal.dot(wi);
+        if cos_theta <= 0.0 {
+            return Color::zero();
+        }
+        self.brdf(rec.normal, wo, wi) * cos_theta
+    }
+
+    fn scattering_pdf(&self, _: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        let cos_theta = rec.normal.dot(scattered.direction.unit_vector());
+        if cos_theta > 0.0 { cos_theta / PI } else { 0.0 }
     }
 }
 
@@ -64,26 +346,456 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<(Color, Ray)> {
-        let reflected = r_in.direction.reflect(rec.normal);
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        self.scatter_with_reflectance(r_in, rec, self.albedo)
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        self.eval_with_reflectance(r_in, rec, scattered, self.albedo)
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        if self.fuzz <= 0.0 {
+            return 0.0;
+        }
+
+        // 散射方向是以反射方向为球心、fuzz 为半径的球面上均匀分布的点的方向，
+        // 将球面上的面积密度换算到立体角上，对光线与球面的（最多两个）交点求和
+        let reflected = r_in.direction.unit_vector().reflect(rec.normal);
+        let cos_theta = scattered.direction.unit_vector().dot(reflected);
+        let s_squared = self.fuzz * self.fuzz - (1.0 - cos_theta * cos_theta);
+        if s_squared <= 0.0 {
+            return 0.0;
+        }
+        let s = s_squared.sqrt();
+
+        if self.fuzz < 1.0 {
+            // 原点在球外，只有指向反射方向一侧的方向与球面相交，且交点有两个
+            if cos_theta <= 0.0 {
+                return 0.0;
+            }
+            (cos_theta * cos_theta + s_squared) / (2.0 * PI * self.fuzz * s)
+        } else {
+            // 原点在球内（或球面上），只有一个交点
+            let t = cos_theta + s;
+            t * t / (4.0 * PI * self.fuzz * s)
+        }
+    }
+}
+
+impl Coatable for Metal {
+    /// 把反照率当作法线方向上的反射率 F0，反推出对应的（实数）折射率。
+    ///
+    /// 真实金属的折射率是复数，这里只是让薄膜颜色大致合理的近似，需要准确的结果时使用 [`Conductor`]。
+    fn substrate_ior(&self, _: Ray, _: &HitRecord<'_>) -> Color {
+        let channel = |f0: f64| {
+            let r = f0.clamp(0.0, 0.99).sqrt();
+            (1.0 + r) / (1.0 - r)
+        };
+        Color::new(
+            channel(self.albedo.x),
+            channel(self.albedo.y),
+            channel(self.albedo.z),
+        )
+    }
+
+    fn scatter_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        reflectance: Color,
+    ) -> Option<ScatterRecord> {
+        let reflected = r_in.direction.unit_vector().reflect(rec.normal);
+        if self.fuzz <= 0.0 {
+            return Some(ScatterRecord::specular(
+                reflectance,
+                Ray::new(rec.p, reflected),
+            ));
+        }
+
         let scattered = Ray::new(rec.p, reflected + self.fuzz * Vec3::random_unit());
         if scattered.direction.dot(rec.normal) > 0.0 {
-            Some((self.albedo, scattered))
+            let pdf = self.scattering_pdf(r_in, rec, scattered);
+            Some(ScatterRecord::new(reflectance, scattered, pdf))
         } else {
             None
         }
     }
+
+    fn eval_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        scattered: Ray,
+        reflectance: Color,
+    ) -> Color {
+        if scattered.direction.dot(rec.normal) <= 0.0 {
+            return Color::zero();
+        }
+        reflectance * self.scattering_pdf(r_in, rec, scattered)
+    }
+}
+
+/// 基于 GGX 微表面模型的导体材质
+///
+/// 使用 Smith 遮蔽函数和可见法线采样，菲涅尔项由复折射率计算，粗糙的金属在掠射角下也能保持正确的外观。
+/// 各向异性粗糙度的 x 轴为表面切线 `∂p/∂u` 绕法线旋转 `rotation` 后的方向，
+/// 因此拉丝等效果沿着物体的表面坐标连续变化。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Conductor, Material};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// // 沿表面坐标 v 方向拉丝的铝
+/// let brushed = Conductor::anisotropic(
+///     Color::new(1.657, 0.880, 0.521),
+///     Color::new(9.224, 6.270, 4.837),
+///     0.05,
+///     0.3,
+/// )
+/// .rotation(90.0);
+///
+/// for cos_theta in [1.0f64, 0.5, 0.1] {
+///     let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+///     let r_in = Ray::new(
+///         Point3::new(-sin_theta, cos_theta, 0.0),
+///         Vec3::new(sin_theta, -cos_theta, 0.0),
+///     );
+///     let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &brushed);
+///
+///     // 按材质自身的分布采样，估计 ∫ f cosθ dω，即该入射方向上的反照率
+///     let n = 20000;
+///     let mut albedo = Color::zero();
+///     for _ in 0..n {
+///         let Some(srec) = brushed.scatter(r_in, &rec) else {
+///             continue;
+///         };
+///         let pdf = brushed.scattering_pdf(r_in, &rec, srec.scattered);
+///         assert!((srec.pdf - pdf).abs() <= 1e-9 * pdf);
+///         albedo += brushed.eval(r_in, &rec, srec.scattered) / pdf;
+///     }
+///     // 金属只反射不透射，反射的能量不会超过入射的能量
+///     let albedo = albedo / n as f64;
+///     assert!(albedo.x < 1.0 && albedo.y < 1.0 && albedo.z < 1.0, "{albedo:?}");
+///     assert!(albedo.x > 0.5, "{albedo:?}");
+/// }
+/// ```
+pub struct Conductor {
+    /// 复折射率的实部（分别对应 r、g、b 三个通道）
+    pub eta: Color,
+    /// 复折射率的虚部，即消光系数
+    pub k: Color,
+    /// 微表面法线分布
+    pub distribution: TrowbridgeReitz,
+    /// 各向异性粗糙度的 x 轴相对于表面切线 `∂p/∂u` 绕法线旋转的角度，单位为弧度
+    pub rotation: f64,
+}
+
+impl Conductor {
+    /// 创建一个新的各向同性导体材质实例，`roughness` 即 GGX 分布的 α。
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self::anisotropic(eta, k, roughness, roughness)
+    }
+
+    /// 创建一个新的各向异性导体材质实例。
+    pub fn anisotropic(eta: Color, k: Color, roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: TrowbridgeReitz::new(roughness_x, roughness_y),
+            rotation: 0.0,
+        }
+    }
+
+    /// 设置各向异性粗糙度的 x 轴相对于表面切线绕法线旋转的角度，单位为角度，默认为 0。
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees.to_radians();
+        self
+    }
+
+    /// 金
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    /// 铜
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    /// 铝
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    /// 银
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    /// 把入射光线和散射光线的方向转换到以法线为 z 轴的局部坐标系，返回 (wo, wi)。
+    fn local_directions(&self, rec: &HitRecord<'_>, r_in: Ray, scattered: Ray) -> (Vec3, Vec3) {
+        let onb = tangent_frame(rec, self.rotation);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        let wi = onb.to_local(scattered.direction.unit_vector());
+        (wo, wi)
+    }
+
+    /// 使用给定的菲涅尔项进行散射，`fresnel` 的参数为入射方向与微表面法线夹角的余弦。
+    fn scatter_with(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        fresnel: impl Fn(f64) -> Color,
+    ) -> Option<ScatterRecord> {
+        let onb = tangent_frame(rec, self.rotation);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+            let attenuation = fresnel(wo.z);
+            return Some(ScatterRecord::specular(
+                attenuation,
+                Ray::new(rec.p, onb.transform(wi)),
+            ));
+        }
+
+        // 采样可见的微表面法线，再沿微表面法线反射
+        let wm = self.distribution.sample_visible_normal(wo);
+        let wi = -wo + 2.0 * wo.dot(wm) * wm;
+        if wi.z <= 0.0 {
+            return None;
+        }
+
+        // f * cosθi / pdf 化简后为 F * G / G1(wo)
+        let attenuation =
+            fresnel(wo.dot(wm)) * self.distribution.g(wo, wi) / self.distribution.g1(wo);
+        let pdf = self.distribution.d_visible(wo, wm) / (4.0 * wo.dot(wm));
+        Some(ScatterRecord::new(
+            attenuation,
+            Ray::new(rec.p, onb.transform(wi)),
+            pdf,
+        ))
+    }
+
+    /// 使用给定的菲涅尔项计算 `BSDF * cosθ`。
+    fn eval_with(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        scattered: Ray,
+        fresnel: impl Fn(f64) -> Color,
+    ) -> Color {
+        if self.distribution.is_smooth() {
+            return Color::zero();
+        }
+        let (wo, wi) = self.local_directions(rec, r_in, scattered);
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return Color::zero();
+        }
+
+        // f * cosθi = D * G * F / (4 * cosθo)
+        let wm = (wo + wi).unit_vector();
+        fresnel(wo.dot(wm)) * self.distribution.d(wm) * self.distribution.g(wo, wi) / (4.0 * wo.z)
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        self.scatter_with(r_in, rec, |cos_theta| {
+            fresnel_conductor(cos_theta, self.eta, self.k)
+        })
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        self.eval_with(r_in, rec, scattered, |cos_theta| {
+            fresnel_conductor(cos_theta, self.eta, self.k)
+        })
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+        let (wo, wi) = self.local_directions(rec, r_in, scattered);
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+
+        let wm = (wo + wi).unit_vector();
+        self.distribution.d_visible(wo, wm) / (4.0 * wo.dot(wm))
+    }
+}
+
+impl Coatable for Conductor {
+    fn substrate_ior(&self, _: Ray, _: &HitRecord<'_>) -> Color {
+        self.eta
+    }
+
+    fn substrate_extinction(&self, _: Ray, _: &HitRecord<'_>) -> Color {
+        self.k
+    }
+
+    /// 粗糙表面上每个微表面都使用同一个反射率，忽略了薄膜反射率随微表面朝向的变化。
+    fn scatter_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        reflectance: Color,
+    ) -> Option<ScatterRecord> {
+        self.scatter_with(r_in, rec, |_| reflectance)
+    }
+
+    fn eval_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        scattered: Ray,
+        reflectance: Color,
+    ) -> Color {
+        self.eval_with(r_in, rec, scattered, |_| reflectance)
+    }
+}
+
+/// 微表面材质使用的局部坐标系：z 轴为（着色）法线，x 轴为表面切线 `∂p/∂u` 正交化后再绕法线旋转
+/// `rotation` 弧度的方向，使各向异性粗糙度的方向跟随物体的表面坐标。
+fn tangent_frame(rec: &HitRecord<'_>, rotation: f64) -> Onb {
+    let onb = Onb::from_tangent(rec.normal, rec.dpdu);
+    if rotation == 0.0 {
+        return onb;
+    }
+    let (sin, cos) = rotation.sin_cos();
+    Onb::from_tangent(rec.normal, cos * onb.u() + sin * onb.v())
 }
 
 /// 电介质材质
+///
+/// 设置了色散模型时，光谱渲染中折射率随入射光线的波长变化，三棱镜、钻石等会呈现出彩虹色；
+/// RGB 渲染时使用 `refraction_index`。
+///
+/// 设置了吸收系数时，光线在物体内部按照 Beer–Lambert 定律衰减，可以用来渲染有色玻璃和液体。
+///
+/// 折射时使用的相对折射率由物体外侧介质的折射率 [`HitRecord::exterior_ior`] 决定，
+/// 互相重叠的电介质（例如杯中的水与玻璃杯）通过 `priority` 决定重叠部分属于哪一个。
 pub struct Dielectric {
     pub refraction_index: f64,
+    pub dispersion: Option<Dispersion>,
+    /// 内部介质的吸收系数
+    pub absorption: Color,
+    /// 内部介质的优先级，数值越大越优先
+    pub priority: i32,
 }
 
 impl Dielectric {
+    /// 计算 RGB 渲染使用的折射率时采用的波长（夫琅禾费 d 线），单位为纳米
+    const REFERENCE_WAVELENGTH: f64 = 587.6;
+
     /// 创建一个新的电介质材质实例。
     pub fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self {
+            refraction_index,
+            dispersion: None,
+            absorption: Color::zero(),
+            priority: 0,
+        }
+    }
+
+    /// 创建一个折射率随波长变化的电介质材质实例。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::material::Dielectric;
+    /// use ray_tracing_in_one_weekend::spectrum::Dispersion;
+    ///
+    /// let diamond = Dielectric::with_dispersion(Dispersion::diamond());
+    /// assert!((diamond.refraction_index - 2.417).abs() < 1e-3);
+    /// ```
+    pub fn with_dispersion(dispersion: Dispersion) -> Self {
+        Self {
+            refraction_index: dispersion.refraction_index(Self::REFERENCE_WAVELENGTH),
+            dispersion: Some(dispersion),
+            absorption: Color::zero(),
+            priority: 0,
+        }
+    }
+
+    /// 设置内部介质的吸收系数。
+    pub fn absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// 设置内部介质的优先级。
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 按照光线在内部传播 `distance` 距离后的颜色设置吸收系数。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::Color;
+    /// use ray_tracing_in_one_weekend::material::Dielectric;
+    ///
+    /// let green_glass = Dielectric::new(1.5).transmission_color(Color::new(0.5, 0.9, 0.5), 2.0);
+    /// assert!(green_glass.absorption.y < green_glass.absorption.x);
+    /// ```
+    pub fn transmission_color(self, color: Color, distance: f64) -> Self {
+        self.absorption(absorption_from_color(color, distance))
+    }
+
+    /// 波长为 `wavelength`（单位为纳米）的光的折射率，没有波长或色散模型时使用固定的折射率。
+    pub fn refraction_index_at(&self, wavelength: Option<f64>) -> f64 {
+        match (self.dispersion, wavelength) {
+            (Some(dispersion), Some(lambda)) => dispersion.refraction_index(lambda),
+            _ => self.refraction_index,
+        }
+    }
+
+    /// 入射一侧与折射一侧的折射率之比。
+    fn relative_ior(&self, r_in: Ray, rec: &HitRecord<'_>) -> f64 {
+        let refraction_index = self.refraction_index_at(r_in.wavelength);
+        if rec.front_face {
+            rec.exterior_ior / refraction_index
+        } else {
+            refraction_index / rec.exterior_ior
+        }
+    }
+
+    /// 入射光线在碰撞点发生反射（而不是折射）的概率，无法折射时为 1。
+    fn reflect_probability(&self, r_in: Ray, rec: &HitRecord<'_>) -> f64 {
+        let ri = self.relative_ior(r_in, rec);
+        let cos_theta = (-r_in.direction.unit_vector()).dot(rec.normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        let cannot_refract = ri * sin_theta > 1.0;
+        if cannot_refract {
+            1.0
+        } else {
+            Self::reflectance(cos_theta, ri)
+        }
     }
 
     // Schlick 近似计算反射率
@@ -98,26 +810,1597 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<(Color, Ray)> {
-        let attenuation = Color::one();
-        let ri = if rec.front_face {
-            1.0 / self.refraction_index
-        } else {
-            self.refraction_index
-        };
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let reflectance = Color::one() * self.reflect_probability(r_in, rec);
+        self.scatter_with_reflectance(r_in, rec, reflectance)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.dispersion.is_some()
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(Medium {
+            refraction_index: self.refraction_index,
+            absorption: self.absorption,
+            priority: self.priority,
+        })
+    }
+}
+
+impl Coatable for Dielectric {
+    fn substrate_ior(&self, r_in: Ray, _: &HitRecord<'_>) -> Color {
+        Color::one() * self.refraction_index_at(r_in.wavelength)
+    }
 
+    /// 按照反射率各通道的平均值随机选择反射或折射，再按通道补偿权重；无法折射时总是反射。
+    fn scatter_with_reflectance(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord<'_>,
+        reflectance: Color,
+    ) -> Option<ScatterRecord> {
+        let ri = self.relative_ior(r_in, rec);
         let unit_direction = r_in.direction.unit_vector();
-        let cos_theta = (-unit_direction).dot(rec.normal).min(1.0);
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let reflected = Ray::new(rec.p, unit_direction.reflect(rec.normal));
+        if self.reflect_probability(r_in, rec) >= 1.0 {
+            return Some(ScatterRecord::specular(Color::one(), reflected));
+        }
 
-        let cannot_refract = ri * sin_theta > 1.0;
-        let direction = if cannot_refract || Self::reflectance(cos_theta, ri) > random_double() {
-            unit_direction.reflect(rec.normal)
+        let p = ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(0.0, 1.0);
+        if random_double() < p {
+            Some(ScatterRecord::specular(reflectance / p, reflected))
         } else {
-            unit_direction.refract(rec.normal, ri)
-        };
+            let refracted = Ray::new(rec.p, unit_direction.refract(rec.normal, ri));
+            Some(ScatterRecord::specular(
+                (Color::one() - reflectance) / (1.0 - p),
+                refracted,
+            ))
+        }
+    }
+}
 
-        let scattered = Ray::new(rec.p, direction);
-        Some((attenuation, scattered))
+/// 由光线传播 `distance` 距离后的颜色反推吸收系数，颜色分量为 0 时视为完全吸收。
+fn absorption_from_color(color: Color, distance: f64) -> Color {
+    let channel = |c: f64| {
+        if c <= 0.0 {
+            f64::INFINITY
+        } else {
+            -c.min(1.0).ln() / distance
+        }
+    };
+    Color::new(channel(color.x), channel(color.y), channel(color.z))
+}
+
+/// 基于 GGX 微表面模型的粗糙电介质材质，可以用来渲染磨砂玻璃、冰等。
+///
+/// 微表面上的反射与折射概率由与 [`Dielectric`] 相同的 Schlick 近似决定。
+/// 与 [`Dielectric`] 一样，折射时不按折射率的平方缩放辐亮度。
+/// 各向异性粗糙度的方向与 [`Conductor`] 相同，由表面切线和 `rotation` 决定。
+/// 粗糙度为 0 时与 [`Dielectric`] 完全相同。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Dielectric, Material, RoughDielectric};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// let frosted = RoughDielectric::new(1.5, 0.3);
+/// let r_in = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+/// let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &frosted);
+///
+/// // 反射和折射的能量加起来不超过入射的能量
+/// let n = 20000;
+/// let mut albedo = Color::zero();
+/// for _ in 0..n {
+///     let Some(srec) = frosted.scatter(r_in, &rec) else {
+///         continue;
+///     };
+///     let pdf = frosted.scattering_pdf(r_in, &rec, srec.scattered);
+///     assert!((srec.pdf - pdf).abs() <= 1e-9 * pdf);
+///     albedo += frosted.eval(r_in, &rec, srec.scattered) / pdf;
+/// }
+/// let albedo = albedo.x / n as f64;
+/// assert!(albedo > 0.8 && albedo < 1.0, "{albedo}");
+///
+/// // 粗糙度为 0 时只在镜面反射和折射方向上散射，反射的比例与光滑的电介质相同
+/// let smooth = RoughDielectric::new(1.5, 0.0);
+/// let glass = Dielectric::new(1.5);
+/// let d = r_in.direction.unit_vector();
+/// let (reflected, refracted) = (d.reflect(Vec3::unit_y()), d.refract(Vec3::unit_y(), 1.0 / 1.5));
+/// let reflect_fraction = |material: &dyn Material| {
+///     let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, material);
+///     let mut reflections = 0;
+///     for _ in 0..n {
+///         let srec = material.scatter(r_in, &rec).unwrap();
+///         let w = srec.scattered.direction.unit_vector();
+///         assert!(srec.is_specular);
+///         assert!((w - reflected).length() < 1e-9 || (w - refracted).length() < 1e-9);
+///         if w.y > 0.0 {
+///             reflections += 1;
+///         }
+///     }
+///     reflections as f64 / n as f64
+/// };
+/// assert!((reflect_fraction(&smooth) - reflect_fraction(&glass)).abs() < 0.015);
+/// ```
+pub struct RoughDielectric {
+    pub refraction_index: f64,
+    /// 微表面法线分布
+    pub distribution: TrowbridgeReitz,
+    /// 各向异性粗糙度的 x 轴相对于表面切线 `∂p/∂u` 绕法线旋转的角度，单位为弧度
+    pub rotation: f64,
+    /// 内部介质的吸收系数
+    pub absorption: Color,
+    /// 内部介质的优先级，数值越大越优先
+    pub priority: i32,
+}
+
+impl RoughDielectric {
+    /// 创建一个新的各向同性粗糙电介质材质实例，`roughness` 即 GGX 分布的 α。
+    pub fn new(refraction_index: f64, roughness: f64) -> Self {
+        Self::anisotropic(refraction_index, roughness, roughness)
+    }
+
+    /// 创建一个新的各向异性粗糙电介质材质实例。
+    pub fn anisotropic(refraction_index: f64, roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            refraction_index,
+            distribution: TrowbridgeReitz::new(roughness_x, roughness_y),
+            rotation: 0.0,
+            absorption: Color::zero(),
+            priority: 0,
+        }
+    }
+
+    /// 设置各向异性粗糙度的 x 轴相对于表面切线绕法线旋转的角度，单位为角度，默认为 0。
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees.to_radians();
+        self
+    }
+
+    /// 设置内部介质的优先级。
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 设置内部介质的吸收系数。
+    pub fn absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// 按照光线在内部传播 `distance` 距离后的颜色设置吸收系数。
+    pub fn transmission_color(self, color: Color, distance: f64) -> Self {
+        self.absorption(absorption_from_color(color, distance))
+    }
+
+    /// 散射方向一侧与入射方向一侧的折射率之比。
+    #[inline]
+    fn eta(&self, rec: &HitRecord<'_>) -> f64 {
+        if rec.front_face {
+            self.refraction_index / rec.exterior_ior
+        } else {
+            rec.exterior_ior / self.refraction_index
+        }
+    }
+
+    /// 入射方向与微表面法线夹角的余弦为 `cos_theta` 时的反射率，发生全内反射时为 1。
+    fn fresnel(cos_theta: f64, eta: f64) -> f64 {
+        let ri = 1.0 / eta;
+        let sin2_theta_t = ri * ri * (1.0 - cos_theta * cos_theta);
+        if sin2_theta_t >= 1.0 {
+            1.0
+        } else {
+            Dielectric::reflectance(cos_theta, ri)
+        }
+    }
+
+    /// 由入射和散射方向计算微表面法线，返回值的 z 分量总是非负。
+    ///
+    /// 折射时还会返回半向量雅可比行列式中的分母 `(wi·wm + wo·wm / η)²`，
+    /// 微表面背向任一方向时返回 None。
+    fn half_vector(wo: Vec3, wi: Vec3, eta: f64) -> Option<(Vec3, Option<f64>)> {
+        let reflect = wi.z > 0.0;
+        let wm = if reflect { wo + wi } else { wi * eta + wo };
+        if wm.near_zero() {
+            return None;
+        }
+        let wm = wm.unit_vector();
+        let wm = if wm.z < 0.0 { -wm } else { wm };
+
+        if reflect {
+            return (wo.dot(wm) > 0.0).then_some((wm, None));
+        }
+        if wo.dot(wm) <= 0.0 || wi.dot(wm) >= 0.0 {
+            return None;
+        }
+        let denom = wi.dot(wm) + wo.dot(wm) / eta;
+        Some((wm, Some(denom * denom)))
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        if self.distribution.is_smooth() {
+            return Dielectric::new(self.refraction_index).scatter(r_in, rec);
+        }
+
+        let onb = tangent_frame(rec, self.rotation);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        let eta = self.eta(rec);
+        let wm = self.distribution.sample_visible_normal(wo);
+        let cos_theta = wo.dot(wm);
+        let reflectance = Self::fresnel(cos_theta, eta);
+
+        // 按照反射率在反射和折射之间随机选择，两种情况下 f * cosθi / pdf 都化简为 G / G1(wo)
+        let (wi, pdf) = if random_double() < reflectance {
+            let wi = -wo + 2.0 * cos_theta * wm;
+            if wi.z <= 0.0 {
+                return None;
+            }
+            let pdf = self.distribution.d_visible(wo, wm) / (4.0 * cos_theta) * reflectance;
+            (wi, pdf)
+        } else {
+            let wi = (-wo).refract(wm, 1.0 / eta);
+            if wi.z >= 0.0 {
+                return None;
+            }
+            let wi = wi.unit_vector();
+            let denom = wi.dot(wm) + cos_theta / eta;
+            let pdf = self.distribution.d_visible(wo, wm) * wi.dot(wm).abs() / (denom * denom)
+                * (1.0 - reflectance);
+            (wi, pdf)
+        };
+
+        let attenuation = Color::one() * self.distribution.g(wo, wi) / self.distribution.g1(wo);
+        Some(ScatterRecord::new(
+            attenuation,
+            Ray::new(rec.p, onb.transform(wi)),
+            pdf,
+        ))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        if self.distribution.is_smooth() {
+            return Color::zero();
+        }
+        let onb = tangent_frame(rec, self.rotation);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        let wi = onb.to_local(scattered.direction.unit_vector());
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return Color::zero();
+        }
+
+        let eta = self.eta(rec);
+        let Some((wm, denom)) = Self::half_vector(wo, wi, eta) else {
+            return Color::zero();
+        };
+        let reflectance = Self::fresnel(wo.dot(wm), eta);
+        let dg = self.distribution.d(wm) * self.distribution.g(wo, wi);
+
+        let value = match denom {
+            // 反射：f * cosθi = D * G * F / (4 * cosθo)
+            None => dg * reflectance / (4.0 * wo.z),
+            // 折射：f * |cosθi| = (1 - F) * D * G * |wi·wm| * |wo·wm| / (cosθo * denom)
+            Some(denom) => {
+                (1.0 - reflectance) * dg * wi.dot(wm).abs() * wo.dot(wm) / (wo.z * denom)
+            }
+        };
+        Color::one() * value
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.0;
+        }
+        let onb = tangent_frame(rec, self.rotation);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        let wi = onb.to_local(scattered.direction.unit_vector());
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return 0.0;
+        }
+
+        let eta = self.eta(rec);
+        let Some((wm, denom)) = Self::half_vector(wo, wi, eta) else {
+            return 0.0;
+        };
+        let reflectance = Self::fresnel(wo.dot(wm), eta);
+        let d_visible = self.distribution.d_visible(wo, wm);
+
+        match denom {
+            None => d_visible / (4.0 * wo.dot(wm)) * reflectance,
+            Some(denom) => d_visible * wi.dot(wm).abs() / denom * (1.0 - reflectance),
+        }
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        Some(Medium {
+            refraction_index: self.refraction_index,
+            absorption: self.absorption,
+            priority: self.priority,
+        })
+    }
+}
+
+/// Schlick 近似的权重 `(1 - cosθ)⁵`
+#[inline]
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5)
+}
+
+/// 使用 Schlick 近似菲涅尔项的 GGX 微表面反射，返回 `f * cosθi` 以及可见法线采样的概率密度。
+///
+/// 方向都位于以法线为 z 轴的局部坐标系中。
+fn microfacet_reflection(
+    distribution: &TrowbridgeReitz,
+    f0: Color,
+    wo: Vec3,
+    wi: Vec3,
+) -> (Color, f64) {
+    if wo.z <= 0.0 || wi.z <= 0.0 {
+        return (Color::zero(), 0.0);
+    }
+    let wm = (wo + wi).unit_vector();
+    let cos_theta = wo.dot(wm);
+    let fresnel = f0 + (Color::one() - f0) * schlick_weight(cos_theta);
+    let f = fresnel * distribution.d(wm) * distribution.g(wo, wi) / (4.0 * wo.z);
+    let pdf = distribution.d_visible(wo, wm) / (4.0 * cos_theta);
+    (f, pdf)
+}
+
+/// 迪士尼风格的原则化（Principled）材质
+///
+/// 由基础色、金属度、粗糙度、高光、清漆、光泽（sheen）和透射等参数描述，
+/// 内部混合了漫反射、镜面反射、清漆和透射几个波瓣，便于对照其他软件制作的资产。
+/// 粗糙度为 0 时也按照非常光滑的微表面处理，不会产生镜面散射。
+///
+/// 漫反射波瓣使用能量守恒的 [`OrenNayar`] 模型，并扣除在表面被镜面反射的部分；
+/// 清漆层反射的光同样不会再到达下方的波瓣，因此除光泽外各个波瓣加起来不会产生能量。
+/// 光泽是叠加在掠射角处的额外高光，与迪士尼模型一致。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Material, Principled};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// let car_paint = Principled::new(Color::new(0.6, 0.05, 0.05))
+///     .roughness(0.4)
+///     .clearcoat(1.0);
+///
+/// // 白色的基础色下，漫反射、镜面反射、透射和清漆加起来也不会反射出比入射更多的能量
+/// let white = Principled::new(Color::one())
+///     .roughness(0.5)
+///     .metallic(0.3)
+///     .transmission(0.2, 1.5)
+///     .clearcoat(1.0);
+/// for material in [car_paint, white] {
+///     for cos_theta in [1.0f64, 0.5, 0.1] {
+///         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+///         let r_in = Ray::new(
+///             Point3::new(-sin_theta, cos_theta, 0.0),
+///             Vec3::new(sin_theta, -cos_theta, 0.0),
+///         );
+///         let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &material);
+///
+///         let n = 20000;
+///         let mut albedo = Color::zero();
+///         for _ in 0..n {
+///             let Some(srec) = material.scatter(r_in, &rec) else {
+///                 continue;
+///             };
+///             // 采样时使用的是所有波瓣混合后的概率密度
+///             let pdf = material.scattering_pdf(r_in, &rec, srec.scattered);
+///             assert!((srec.pdf - pdf).abs() <= 1e-9 * pdf);
+///             albedo += material.eval(r_in, &rec, srec.scattered) / pdf;
+///         }
+///         let albedo = albedo / n as f64;
+///         assert!(albedo.x.max(albedo.y).max(albedo.z) < 1.0, "{albedo:?}");
+///     }
+/// }
+/// ```
+pub struct Principled {
+    /// 基础色，电介质的漫反射颜色或金属的反射颜色
+    pub base_color: Color,
+    /// 金属度，取值范围 [0, 1]
+    pub metallic: f64,
+    /// 粗糙度，取值范围 [0, 1]，GGX 分布的 α 为粗糙度的平方
+    pub roughness: f64,
+    /// 电介质的镜面反射强度，0.5 对应折射率约为 1.5 的反射率
+    pub specular: f64,
+    /// 掠射角处的光泽强度，用于模拟布料
+    pub sheen: f64,
+    /// 光泽向基础色着色的程度
+    pub sheen_tint: f64,
+    /// 清漆层的强度，取值范围 [0, 1]
+    pub clearcoat: f64,
+    /// 清漆层的粗糙度
+    pub clearcoat_roughness: f64,
+    /// 透射程度，取值范围 [0, 1]
+    pub transmission: f64,
+    /// 透射时使用的折射率
+    pub ior: f64,
+}
+
+impl Principled {
+    /// 粗糙度换算得到的 α 的下限
+    const MIN_ALPHA: f64 = 1e-3;
+
+    /// 创建一个新的原则化材质实例，其余参数使用默认值。
+    pub fn new(base_color: Color) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            sheen: 0.0,
+            sheen_tint: 0.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            transmission: 0.0,
+            ior: 1.5,
+        }
+    }
+
+    /// 设置金属度。
+    pub fn metallic(mut self, metallic: f64) -> Self {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置粗糙度。
+    pub fn roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置电介质的镜面反射强度。
+    pub fn specular(mut self, specular: f64) -> Self {
+        self.specular = specular.max(0.0);
+        self
+    }
+
+    /// 设置光泽强度以及光泽向基础色着色的程度。
+    pub fn sheen(mut self, sheen: f64, sheen_tint: f64) -> Self {
+        self.sheen = sheen.max(0.0);
+        self.sheen_tint = sheen_tint.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置清漆层的强度。
+    pub fn clearcoat(mut self, clearcoat: f64) -> Self {
+        self.clearcoat = clearcoat.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置清漆层的粗糙度。
+    pub fn clearcoat_roughness(mut self, clearcoat_roughness: f64) -> Self {
+        self.clearcoat_roughness = clearcoat_roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置透射程度以及透射时使用的折射率。
+    pub fn transmission(mut self, transmission: f64, ior: f64) -> Self {
+        self.transmission = transmission.clamp(0.0, 1.0);
+        self.ior = ior;
+        self
+    }
+
+    /// 主镜面反射与透射使用的法线分布
+    fn distribution(&self) -> TrowbridgeReitz {
+        let alpha = (self.roughness * self.roughness).max(Self::MIN_ALPHA);
+        TrowbridgeReitz::new(alpha, alpha)
+    }
+
+    /// 清漆层使用的法线分布
+    fn clearcoat_distribution(&self) -> TrowbridgeReitz {
+        let alpha = (self.clearcoat_roughness * self.clearcoat_roughness).max(Self::MIN_ALPHA);
+        TrowbridgeReitz::new(alpha, alpha)
+    }
+
+    /// 透射波瓣，即一个粗糙电介质，其中折射部分再乘以基础色
+    fn glass(&self) -> RoughDielectric {
+        RoughDielectric {
+            refraction_index: self.ior,
+            distribution: self.distribution(),
+            rotation: 0.0,
+            absorption: Color::zero(),
+            priority: 0,
+        }
+    }
+
+    /// 漫反射、镜面反射、透射和清漆四个波瓣的权重
+    fn lobe_weights(&self) -> [f64; 4] {
+        let diffuse = (1.0 - self.metallic) * (1.0 - self.transmission);
+        let transmission = (1.0 - self.metallic) * self.transmission;
+        [
+            diffuse,
+            1.0 - transmission,
+            transmission,
+            0.25 * self.clearcoat,
+        ]
+    }
+
+    /// 采样时选择各个波瓣的概率
+    fn lobe_probabilities(&self) -> [f64; 4] {
+        let [diffuse, _, transmission, clearcoat] = self.lobe_weights();
+        let specular = self.metallic + (1.0 - self.metallic) * (1.0 - self.transmission) * 0.3;
+        let weights = [diffuse, specular, transmission, clearcoat];
+        let total: f64 = weights.iter().sum();
+        weights.map(|weight| weight / total)
+    }
+
+    /// 光线以 `cos_theta` 穿过清漆层时没有被清漆反射的比例
+    fn clearcoat_transmittance(&self, cos_theta: f64) -> f64 {
+        let [_, _, _, clearcoat] = self.lobe_weights();
+        1.0 - clearcoat * (0.04 + 0.96 * schlick_weight(cos_theta))
+    }
+
+    /// 镜面反射在法线方向上的反射率
+    fn specular_f0(&self) -> Color {
+        let dielectric = Color::one() * (0.08 * self.specular);
+        (1.0 - self.metallic) * dielectric + self.metallic * self.base_color
+    }
+
+    /// 所有波瓣的 `f * cosθi` 之和以及对应的混合概率密度。
+    fn evaluate(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> (Color, f64) {
+        let onb = tangent_frame(rec, 0.0);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        let wi = onb.to_local(scattered.direction.unit_vector());
+        if wo.z <= 0.0 || wi.z == 0.0 {
+            return (Color::zero(), 0.0);
+        }
+
+        let [w_diffuse, w_specular, w_transmission, w_clearcoat] = self.lobe_weights();
+        let [p_diffuse, p_specular, p_transmission, p_clearcoat] = self.lobe_probabilities();
+        let mut f = Color::zero();
+        let mut pdf = 0.0;
+
+        // 透射波瓣同时包含反射和折射，折射部分按基础色着色
+        if w_transmission > 0.0 {
+            let glass = self.glass();
+            let tint = if wi.z < 0.0 {
+                self.base_color
+            } else {
+                Color::one()
+            };
+            f += w_transmission * tint * glass.eval(r_in, rec, scattered);
+            pdf += p_transmission * glass.scattering_pdf(r_in, rec, scattered);
+        }
+
+        // 清漆层反射掉的光不会到达下方的波瓣
+        let coat = self.clearcoat_transmittance(wo.z) * self.clearcoat_transmittance(wi.z.abs());
+        if wi.z < 0.0 {
+            return (f * coat, pdf);
+        }
+
+        if w_diffuse > 0.0 {
+            // 能量守恒的 Oren–Nayar 漫反射，再扣除在表面被镜面反射、没有进入或离开表面的部分
+            let f0 = 0.08 * self.specular;
+            let fresnel = |cos_theta: f64| f0 + (1.0 - f0) * schlick_weight(cos_theta);
+            let oren_nayar = OrenNayar::energy_preserving(self.base_color, self.roughness);
+            let diffuse = oren_nayar.brdf(Vec3::unit_z(), wo, wi)
+                * ((1.0 - fresnel(wo.z)) * (1.0 - fresnel(wi.z)));
+            let wh = (wo + wi).unit_vector();
+            let cos_d = wi.dot(wh);
+
+            let lum = luminance(self.base_color);
+            let tint = if lum > 0.0 {
+                self.base_color / lum
+            } else {
+                Color::one()
+            };
+            let sheen_color = (1.0 - self.sheen_tint) * Color::one() + self.sheen_tint * tint;
+            let sheen = self.sheen * sheen_color * schlick_weight(cos_d);
+
+            f += w_diffuse * (diffuse + sheen) * wi.z;
+            pdf += p_diffuse * wi.z / PI;
+        }
+
+        if w_specular > 0.0 {
+            let (f_specular, pdf_specular) =
+                microfacet_reflection(&self.distribution(), self.specular_f0(), wo, wi);
+            f += w_specular * f_specular;
+            pdf += p_specular * pdf_specular;
+        }
+        f *= coat;
+
+        if w_clearcoat > 0.0 {
+            let (f_clearcoat, pdf_clearcoat) =
+                microfacet_reflection(&self.clearcoat_distribution(), Color::one() * 0.04, wo, wi);
+            f += w_clearcoat * f_clearcoat;
+            pdf += p_clearcoat * pdf_clearcoat;
+        }
+
+        (f, pdf)
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let onb = tangent_frame(rec, 0.0);
+        let wo = onb.to_local(-r_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return None;
+        }
+
+        // 按概率选择一个波瓣采样方向，再用所有波瓣的混合概率密度计算权重
+        let [p_diffuse, p_specular, p_transmission, _] = self.lobe_probabilities();
+        let u = random_double();
+        let direction = if u < p_diffuse {
+            let direction = rec.normal + Vec3::random_unit();
+            if direction.near_zero() {
+                rec.normal
+            } else {
+                direction
+            }
+        } else if u < p_diffuse + p_transmission {
+            self.glass().scatter(r_in, rec)?.scattered.direction
+        } else {
+            let distribution = if u < p_diffuse + p_transmission + p_specular {
+                self.distribution()
+            } else {
+                self.clearcoat_distribution()
+            };
+            let wm = distribution.sample_visible_normal(wo);
+            onb.transform(-wo + 2.0 * wo.dot(wm) * wm)
+        };
+
+        let scattered = Ray::new(rec.p, direction);
+        let (f, pdf) = self.evaluate(r_in, rec, scattered);
+        if pdf <= 0.0 || f.near_zero() {
+            return None;
+        }
+        Some(ScatterRecord::new(f / pdf, scattered, pdf))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        self.evaluate(r_in, rec, scattered).0
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        self.evaluate(r_in, rec, scattered).1
+    }
+}
+
+/// 薄膜干涉涂层
+///
+/// 在 [`Conductor`]、[`Metal`] 或 [`Dielectric`] 等表面覆盖一层厚度与光波长相当的透明薄膜，
+/// 薄膜上下两个界面反射的光相互干涉，反射率随波长和角度变化，可以用来渲染肥皂泡、油膜等彩虹色。
+/// RGB 渲染时分别在三个代表波长上计算反射率，光谱渲染时在射线携带的波长上计算。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Conductor, Dielectric, Material, ThinFilm};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// let r_in = Ray::new(Point3::new(-1.0, 2.0, 0.0), Vec3::new(1.0, -2.0, 0.0));
+/// let n = 20000;
+///
+/// // 空气中的肥皂泡：约 400 纳米厚的水膜，泡内仍然是空气。
+/// // 每个通道上反射与透射的能量之和都等于入射的能量
+/// let bubble = ThinFilm::new(Dielectric::new(1.0), 400.0, 1.33);
+/// let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &bubble);
+/// let mut total = Color::zero();
+/// for _ in 0..n {
+///     total += bubble.scatter(r_in, &rec).unwrap().attenuation;
+/// }
+/// let total = total / n as f64;
+/// assert!((total - Color::one()).length() < 0.05, "{total:?}");
+///
+/// // 回火钢表面的氧化膜，基底使用导体的复折射率
+/// let steel = |roughness| {
+///     Conductor::new(Color::new(2.9, 2.9, 2.8), Color::new(3.0, 3.0, 3.1), roughness)
+/// };
+/// let tempered = ThinFilm::new(steel(0.1), 80.0, 2.3);
+/// let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &tempered);
+/// let mut albedo = Color::zero();
+/// for _ in 0..n {
+///     let Some(srec) = tempered.scatter(r_in, &rec) else {
+///         continue;
+///     };
+///     let pdf = tempered.scattering_pdf(r_in, &rec, srec.scattered);
+///     assert!((srec.pdf - pdf).abs() <= 1e-9 * pdf);
+///     albedo += tempered.eval(r_in, &rec, srec.scattered) / pdf;
+/// }
+/// let albedo = albedo / n as f64;
+/// assert!(albedo.x.max(albedo.y).max(albedo.z) < 1.0, "{albedo:?}");
+///
+/// // 厚度为 0 的薄膜不改变光滑基底的反射率
+/// let bare = steel(0.0).scatter(r_in, &rec).unwrap();
+/// let uncoated = ThinFilm::new(steel(0.0), 0.0, 2.3).scatter(r_in, &rec).unwrap();
+/// assert!(bare.is_specular && uncoated.is_specular);
+/// assert!((uncoated.attenuation - bare.attenuation).length() < 1e-9);
+/// ```
+pub struct ThinFilm<B: Coatable> {
+    /// 涂层下方的材质
+    pub base: B,
+    /// 薄膜的厚度，单位为纳米
+    pub thickness: f64,
+    /// 薄膜的折射率
+    pub refraction_index: f64,
+}
+
+impl<B: Coatable> ThinFilm<B> {
+    /// RGB 渲染时 r、g、b 三个通道使用的代表波长，单位为纳米
+    const RGB_WAVELENGTHS: [f64; 3] = [630.0, 532.0, 465.0];
+
+    /// 创建一个新的薄膜涂层实例。
+    pub fn new(base: B, thickness: f64, refraction_index: f64) -> Self {
+        Self {
+            base,
+            thickness,
+            refraction_index,
+        }
+    }
+
+    /// 计算入射光线在碰撞点处的薄膜反射率。
+    fn film_reflectance(&self, r_in: Ray, rec: &HitRecord<'_>) -> Color {
+        let cos_theta = (-r_in.direction.unit_vector())
+            .dot(rec.normal)
+            .clamp(0.0, 1.0);
+        let substrate = self.base.substrate_ior(r_in, rec);
+        let extinction = self.base.substrate_extinction(r_in, rec);
+        let wavelengths = match r_in.wavelength {
+            Some(lambda) => [lambda; 3],
+            None => Self::RGB_WAVELENGTHS,
+        };
+
+        let channel = |substrate: f64, extinction: f64, lambda: f64| {
+            // 从物体内部射向薄膜时，入射一侧是基底，另一侧是外部介质
+            let (n1, n3, k3) = if rec.front_face {
+                (rec.exterior_ior, substrate, extinction)
+            } else {
+                (substrate, rec.exterior_ior, 0.0)
+            };
+            thin_film_reflectance(
+                cos_theta,
+                n1,
+                self.refraction_index,
+                (n3, k3),
+                self.thickness,
+                lambda,
+            )
+        };
+        Color::new(
+            channel(substrate.x, extinction.x, wavelengths[0]),
+            channel(substrate.y, extinction.y, wavelengths[1]),
+            channel(substrate.z, extinction.z, wavelengths[2]),
+        )
+    }
+}
+
+impl<B: Coatable> Material for ThinFilm<B> {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let reflectance = self.film_reflectance(r_in, rec);
+        self.base.scatter_with_reflectance(r_in, rec, reflectance)
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        let reflectance = self.film_reflectance(r_in, rec);
+        self.base
+            .eval_with_reflectance(r_in, rec, scattered, reflectance)
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        self.base.scattering_pdf(r_in, rec, scattered)
+    }
+
+    /// 反射率与波长有关，光谱渲染时只有主波长的反射率是正确的。
+    fn is_dispersive(&self) -> bool {
+        true
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.base.medium()
+    }
+}
+
+/// 计算介质 `n1` 中的光线照射到覆盖在基底上、折射率为 `n2` 的薄膜时的反射率。
+///
+/// 对薄膜内部多次反射的光求和（Airy 公式），结果为 s 偏振与 p 偏振反射率的平均值。
+///
+/// # 参数
+///
+/// * `cos_theta` - 入射角的余弦。
+/// * `substrate` - 基底的复折射率 `(n3, k3)`，`k3` 为 0 时基底是电介质，否则是导体。
+/// * `thickness` - 薄膜厚度，单位为纳米。
+/// * `wavelength` - 光的波长，单位为纳米。
+fn thin_film_reflectance(
+    cos_theta: f64,
+    n1: f64,
+    n2: f64,
+    substrate: (f64, f64),
+    thickness: f64,
+    wavelength: f64,
+) -> f64 {
+    let (n3, k3) = substrate;
+    let sin2_theta1 = 1.0 - cos_theta * cos_theta;
+    let sin2_theta2 = (n1 / n2).powi(2) * sin2_theta1;
+    // 在薄膜上表面发生全内反射时光线全部被反射
+    if sin2_theta2 >= 1.0 {
+        return 1.0;
+    }
+    let cos1 = cos_theta;
+    let cos2 = (1.0 - sin2_theta2).sqrt();
+
+    // 基底一侧的 n3·cosθ3 = sqrt(n3² - n2²·sin²θ2) 是复数 a + bi，
+    // 基底是导体或在基底界面发生全内反射时虚部不为 0
+    let t0 = n3 * n3 - k3 * k3 - n2 * n2 * sin2_theta2;
+    let modulus = (t0 * t0 + 4.0 * n3 * n3 * k3 * k3).sqrt();
+    let a = (0.5 * (modulus + t0)).max(0.0).sqrt();
+    let b = (0.5 * (modulus - t0)).max(0.0).sqrt();
+    // 基底复折射率的平方
+    let (e, f) = (n3 * n3 - k3 * k3, 2.0 * n3 * k3);
+    let divide = |(p, q): (f64, f64), (r, s): (f64, f64)| {
+        let denominator = r * r + s * s;
+        ((p * r + q * s) / denominator, (q * r - p * s) / denominator)
+    };
+
+    // 两次反射之间的光程差对应的相位差
+    let delta = 4.0 * PI * n2 * thickness * cos2 / wavelength;
+
+    // 两个界面的菲涅尔振幅反射系数，符号包含了反射时的半波损失，基底界面的系数是复数
+    let airy = |r12: f64, (re, im): (f64, f64)| {
+        let r23_squared = re * re + im * im;
+        let cross = 2.0 * r12 * (re * delta.cos() - im * delta.sin());
+        let numerator = r12 * r12 + r23_squared + cross;
+        let denominator = 1.0 + r12 * r12 * r23_squared + cross;
+        numerator / denominator
+    };
+    let rs = airy(
+        (n1 * cos1 - n2 * cos2) / (n1 * cos1 + n2 * cos2),
+        divide((n2 * cos2 - a, -b), (n2 * cos2 + a, b)),
+    );
+    let rp = airy(
+        (n2 * cos1 - n1 * cos2) / (n2 * cos1 + n1 * cos2),
+        divide(
+            (e * cos2 - n2 * a, f * cos2 - n2 * b),
+            (e * cos2 + n2 * a, f * cos2 + n2 * b),
+        ),
+    );
+    (0.5 * (rs + rp)).clamp(0.0, 1.0)
+}
+
+/// 分层材质：在任意基底材质上覆盖一层电介质涂层
+///
+/// 涂层的上表面是（可以粗糙的）电介质界面，下方是基底材质，可以用来渲染车漆、清漆木材等表面。
+/// 光线在涂层内部的多次反射通过随机游走模拟（Guo 等，2018），
+/// 因此 [`Material::eval`] 和 [`Material::scattering_pdf`] 返回的都是无偏或近似的随机估计。
+/// 涂层内部可以有吸收，使透过涂层的光带上颜色；基底材质透射的部分会被忽略。
+///
+/// # 示例
+/// ```
+/// use std::f64::consts::PI;
+/// use ray_tracing_in_one_weekend::hittable::HitRecord;
+/// use ray_tracing_in_one_weekend::material::{Lambertian, Layered, Material};
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// // 在红色漆面上覆盖一层光滑的清漆
+/// let car_paint = Layered::new(Lambertian::new(Color::new(0.6, 0.05, 0.05)), 1.5, 0.0);
+/// // 白色基底上的磨砂涂层：涂层没有吸收，光线只会在涂层内部反弹，不会凭空产生能量
+/// let frosted = Layered::new(Lambertian::new(Color::one()), 1.5, 0.3);
+///
+/// let r_in = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+/// let n = 5000;
+/// for material in [car_paint, frosted] {
+///     let rec = HitRecord::new(Point3::zero(), Vec3::unit_y(), 1.0, r_in, &material);
+///
+///     // 随机游走得到的散射权重估计的反照率，镜面反射的部分单独统计
+///     let (mut sampled, mut specular) = (Color::zero(), Color::zero());
+///     for _ in 0..n {
+///         match material.scatter(r_in, &rec) {
+///             Some(srec) if srec.is_specular => specular += srec.attenuation,
+///             Some(srec) => sampled += srec.attenuation,
+///             None => {}
+///         }
+///     }
+///     let (sampled, specular) = (sampled / n as f64, specular / n as f64);
+///     let albedo = sampled + specular;
+///     assert!(albedo.x.max(albedo.y).max(albedo.z) < 1.0, "{albedo:?}");
+///
+///     // 按余弦分布采样方向对 eval 积分，得到的非镜面部分的反照率与散射采样一致
+///     let mut integrated = Color::zero();
+///     for _ in 0..n {
+///         let direction = Vec3::unit_y() + Vec3::random_unit();
+///         let pdf = direction.unit_vector().y / PI;
+///         let scattered = Ray::new(Point3::zero(), direction);
+///         integrated += material.eval(r_in, &rec, scattered) / pdf;
+///     }
+///     let integrated = integrated / n as f64;
+///     assert!((integrated - sampled).length() < 0.06, "{integrated:?} {sampled:?}");
+/// }
+/// ```
+pub struct Layered<B: Material> {
+    /// 涂层下方的材质
+    pub base: B,
+    /// 涂层的折射率
+    pub refraction_index: f64,
+    /// 涂层表面的微表面法线分布
+    pub distribution: TrowbridgeReitz,
+    /// 涂层的吸收系数，以涂层厚度为单位长度
+    pub absorption: Color,
+}
+
+impl<B: Material> Layered<B> {
+    /// 每次估计 BSDF 时随机游走的次数
+    const SAMPLES: usize = 1;
+    /// 随机游走在涂层内部的最大反弹次数
+    const MAX_DEPTH: usize = 10;
+
+    /// 创建一个新的分层材质实例，`roughness` 即涂层表面 GGX 分布的 α。
+    pub fn new(base: B, refraction_index: f64, roughness: f64) -> Self {
+        Self {
+            base,
+            refraction_index,
+            distribution: TrowbridgeReitz::new(roughness, roughness),
+            absorption: Color::zero(),
+        }
+    }
+
+    /// 设置涂层的吸收系数。
+    pub fn absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// 按照光线沿法线方向穿过涂层一次后的颜色设置吸收系数。
+    pub fn tint(self, color: Color) -> Self {
+        self.absorption(absorption_from_color(color, 1.0))
+    }
+
+    /// 涂层的上表面。
+    fn interface(&self) -> RoughDielectric {
+        RoughDielectric {
+            refraction_index: self.refraction_index,
+            distribution: self.distribution,
+            rotation: 0.0,
+            absorption: Color::zero(),
+            priority: 0,
+        }
+    }
+
+    /// 分别从外侧和内侧看涂层上表面的碰撞记录，以及基底材质的碰撞记录。
+    ///
+    /// 无论从哪一侧击中物体，涂层都位于入射光线的一侧。
+    fn records<'a>(&self, rec: &HitRecord<'a>) -> (HitRecord<'a>, HitRecord<'a>, HitRecord<'a>) {
+        let outer = HitRecord {
+            front_face: true,
+            ..*rec
+        };
+        let inner = HitRecord {
+            normal: -rec.normal,
+            front_face: false,
+            ..*rec
+        };
+        let base = HitRecord {
+            front_face: true,
+            exterior_ior: self.refraction_index,
+            ..*rec
+        };
+        (outer, inner, base)
+    }
+
+    /// 光线沿方向 `w` 穿过一次涂层的透射率。
+    fn layer_transmittance(&self, w: Vec3, normal: Vec3) -> Color {
+        let cos_theta = w.unit_vector().dot(normal).abs().max(1e-6);
+        Medium {
+            refraction_index: self.refraction_index,
+            absorption: self.absorption,
+            priority: 0,
+        }
+        .transmittance(1.0 / cos_theta)
+    }
+}
+
+impl<B: Material> Material for Layered<B> {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let coat = self.interface();
+        let (outer, inner, base_rec) = self.records(rec);
+        let ray = |direction: Vec3| Ray::new(rec.p, direction).with_wavelength(r_in.wavelength);
+        let finish = |attenuation: Color, scattered: Ray, is_specular: bool| {
+            Some(if is_specular {
+                ScatterRecord::specular(attenuation, scattered)
+            } else {
+                let pdf = self.scattering_pdf(r_in, rec, scattered);
+                ScatterRecord::new(attenuation, scattered, pdf)
+            })
+        };
+
+        // 在涂层表面直接反射
+        let entered = coat.scatter(r_in, &outer)?;
+        if entered.scattered.direction.dot(rec.normal) > 0.0 {
+            return finish(entered.attenuation, entered.scattered, entered.is_specular);
+        }
+
+        // 在涂层内部的基底与上表面之间来回反弹，直到从上表面离开
+        let mut beta = entered.attenuation;
+        let mut is_specular = entered.is_specular;
+        let mut w = entered.scattered.direction;
+        for _ in 0..Self::MAX_DEPTH {
+            beta = beta * self.layer_transmittance(w, rec.normal);
+            let bounce = self.base.scatter(ray(w), &base_rec)?;
+            if bounce.scattered.direction.dot(rec.normal) <= 0.0 {
+                return None;
+            }
+            beta = beta * bounce.attenuation;
+            is_specular &= bounce.is_specular;
+            w = bounce.scattered.direction;
+
+            beta = beta * self.layer_transmittance(w, rec.normal);
+            let interface = coat.scatter(ray(w), &inner)?;
+            beta = beta * interface.attenuation;
+            is_specular &= interface.is_specular;
+            w = interface.scattered.direction;
+            if w.dot(rec.normal) > 0.0 {
+                return finish(beta, ray(w), is_specular);
+            }
+        }
+        None
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        let wi = scattered.direction.unit_vector();
+        let cos_i = wi.dot(rec.normal);
+        if cos_i <= 0.0 {
+            return Color::zero();
+        }
+        let coat = self.interface();
+        let (outer, inner, base_rec) = self.records(rec);
+        let ray = |direction: Vec3| Ray::new(rec.p, direction).with_wavelength(r_in.wavelength);
+        let is_below = |s: &ScatterRecord| s.scattered.direction.dot(rec.normal) < 0.0;
+        let smooth = coat.distribution.is_smooth();
+        let eta = self.refraction_index / rec.exterior_ior;
+        let mis = MisHeuristic::Power;
+
+        let mut f = coat.eval(r_in, &outer, scattered) * Self::SAMPLES as f64;
+        for _ in 0..Self::SAMPLES {
+            let Some(entered) = coat.scatter(r_in, &outer).filter(is_below) else {
+                continue;
+            };
+            // 从散射方向一侧穿过涂层，得到与基底连接的方向
+            let exit = if smooth {
+                None
+            } else {
+                coat.scatter(ray(-wi), &outer).filter(is_below)
+            };
+            let refracted_up = -(-wi).refract(rec.normal, 1.0 / eta).unit_vector();
+
+            let mut beta = entered.attenuation;
+            let mut w = entered.scattered.direction.unit_vector();
+            for depth in 0..Self::MAX_DEPTH {
+                if depth > 3 {
+                    let survival = beta.x.max(beta.y).max(beta.z).min(1.0);
+                    if random_double() >= survival {
+                        break;
+                    }
+                    beta /= survival;
+                }
+                beta = beta * self.layer_transmittance(w, rec.normal);
+
+                // 把基底上的散射与出射方向连接起来
+                if smooth {
+                    // 光滑界面只有一个方向能折射到出射方向，需要换算立体角
+                    let up = refracted_up;
+                    let transmit = 1.0
+                        - Dielectric::new(self.refraction_index)
+                            .reflect_probability(ray(up), &inner);
+                    let jacobian = cos_i / (eta * eta * up.dot(rec.normal).max(1e-6));
+                    f += beta
+                        * self.base.eval(ray(w), &base_rec, ray(up))
+                        * self.layer_transmittance(up, rec.normal)
+                        * (transmit * jacobian);
+                } else if let Some(exit) = &exit {
+                    let up = -exit.scattered.direction;
+                    let f_base = self.base.eval(ray(w), &base_rec, ray(up));
+                    let base_pdf = self.base.scattering_pdf(ray(w), &base_rec, ray(up));
+                    f += beta
+                        * f_base
+                        * self.layer_transmittance(up, rec.normal)
+                        * coat.eval(ray(up), &inner, scattered)
+                        * (mis.weight(exit.pdf, base_pdf) / exit.pdf);
+                }
+
+                let Some(bounce) = self
+                    .base
+                    .scatter(ray(w), &base_rec)
+                    .filter(|s| !is_below(s))
+                else {
+                    break;
+                };
+                beta = beta * bounce.attenuation;
+                w = bounce.scattered.direction.unit_vector();
+
+                // 从基底采样的方向直接穿出涂层
+                if !smooth {
+                    let weight = if bounce.is_specular {
+                        1.0
+                    } else {
+                        let exit_pdf = coat.scattering_pdf(ray(-wi), &outer, ray(-w));
+                        mis.weight(bounce.pdf, exit_pdf)
+                    };
+                    f += beta
+                        * self.layer_transmittance(w, rec.normal)
+                        * coat.eval(ray(w), &inner, scattered)
+                        * weight;
+                }
+
+                beta = beta * self.layer_transmittance(w, rec.normal);
+                let Some(reflected) = coat.scatter(ray(w), &inner).filter(is_below) else {
+                    break;
+                };
+                beta = beta * reflected.attenuation;
+                w = reflected.scattered.direction.unit_vector();
+            }
+        }
+        f / Self::SAMPLES as f64
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        let wi = scattered.direction.unit_vector();
+        if wi.dot(rec.normal) <= 0.0 {
+            return 0.0;
+        }
+        let coat = self.interface();
+        let (outer, inner, base_rec) = self.records(rec);
+        let ray = |direction: Vec3| Ray::new(rec.p, direction).with_wavelength(r_in.wavelength);
+        let is_below = |s: &ScatterRecord| s.scattered.direction.dot(rec.normal) < 0.0;
+        let mis = MisHeuristic::Power;
+
+        // 涂层表面的反射，加上穿过涂层、在基底反射一次再穿出的近似概率密度
+        let mut pdf = coat.scattering_pdf(r_in, &outer, scattered) * Self::SAMPLES as f64;
+        for _ in 0..Self::SAMPLES {
+            let entered = coat.scatter(r_in, &outer).filter(is_below);
+            let exit = coat.scatter(ray(-wi), &outer).filter(is_below);
+            let (Some(entered), Some(exit)) = (entered, exit) else {
+                continue;
+            };
+            let down = entered.scattered.direction;
+            let up = -exit.scattered.direction;
+
+            if coat.distribution.is_smooth() {
+                pdf += self.base.scattering_pdf(ray(down), &base_rec, ray(up));
+                continue;
+            }
+            let Some(bounce) = self
+                .base
+                .scatter(ray(down), &base_rec)
+                .filter(|s| !is_below(s))
+            else {
+                continue;
+            };
+            if !bounce.is_specular {
+                let base_pdf = self.base.scattering_pdf(ray(down), &base_rec, ray(up));
+                pdf += mis.weight(exit.pdf, base_pdf) * base_pdf;
+            }
+            let exit_pdf = coat.scattering_pdf(bounce.scattered, &inner, scattered);
+            pdf += mis.weight(bounce.pdf, exit_pdf) * exit_pdf;
+        }
+
+        // 与半球上的均匀分布混合，避免估计值为 0
+        0.1 / (2.0 * PI) + 0.9 * pdf / Self::SAMPLES as f64
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+}
+
+/// 混合材质
+///
+/// 按照权重混合两种材质，权重可以是常数，也可以随碰撞点的位置或纹理坐标变化，
+/// 用来在不编写新 BSDF 的情况下得到金属上的锈迹、漆面上的污渍等效果。
+/// 散射时按照权重随机选择其中一种材质。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::Color;
+/// use ray_tracing_in_one_weekend::material::{Lambertian, Metal, MixFactor, MixMaterial};
+///
+/// let metal = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.1));
+/// let rust = Rc::new(Lambertian::new(Color::new(0.4, 0.15, 0.05)));
+///
+/// // 越靠近地面锈迹越多
+/// let rusty = MixMaterial::new(
+///     metal,
+///     rust,
+///     MixFactor::Spatial(Box::new(|p| 1.0 - p.y)),
+/// );
+///
+/// // 按纹理坐标画出条纹，条纹会随物体一起移动
+/// let striped = MixMaterial::new(
+///     Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.1)),
+///     Rc::new(Lambertian::new(Color::new(0.4, 0.15, 0.05))),
+///     MixFactor::Uv(Box::new(|u, _| if (u * 10.0).fract() < 0.5 { 0.0 } else { 1.0 })),
+/// );
+/// ```
+pub struct MixMaterial {
+    /// 权重为 0 时使用的材质
+    pub first: Rc<dyn Material>,
+    /// 权重为 1 时使用的材质
+    pub second: Rc<dyn Material>,
+    /// 第二种材质所占的权重
+    pub factor: MixFactor,
+}
+
+/// [`MixMaterial`] 中第二种材质所占的权重
+pub enum MixFactor {
+    /// 处处相同的权重
+    Constant(f64),
+    /// 由碰撞点位置计算的权重
+    Spatial(Box<dyn Fn(Point3) -> f64>),
+    /// 由碰撞点的表面参数坐标 `(u, v)` 计算的权重，混合结果会跟随物体移动和变形
+    Uv(Box<dyn Fn(f64, f64) -> f64>),
+}
+
+impl MixFactor {
+    /// 碰撞点处的权重，结果限制在 [0, 1] 范围内。
+    pub fn at(&self, rec: &HitRecord<'_>) -> f64 {
+        match self {
+            Self::Constant(factor) => *factor,
+            Self::Spatial(factor) => factor(rec.p),
+            Self::Uv(factor) => factor(rec.u, rec.v),
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+impl MixMaterial {
+    /// 创建一个新的混合材质实例。
+    pub fn new(first: Rc<dyn Material>, second: Rc<dyn Material>, factor: MixFactor) -> Self {
+        Self {
+            first,
+            second,
+            factor,
+        }
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let t = self.factor.at(rec);
+        let chosen = if random_double() < t {
+            &self.second
+        } else {
+            &self.first
+        };
+        let srec = chosen.scatter(r_in, rec)?;
+        // 镜面散射无法被另一种材质采样到，选择概率与混合权重相互抵消
+        if srec.is_specular {
+            return Some(srec);
+        }
+
+        // 两种材质都可能采样到这个方向，按照混合后的 BSDF 和概率密度计算权重
+        let pdf = self.scattering_pdf(r_in, rec, srec.scattered);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let attenuation = self.eval(r_in, rec, srec.scattered) / pdf;
+        Some(ScatterRecord::new(attenuation, srec.scattered, pdf))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        let t = self.factor.at(rec);
+        (1.0 - t) * self.first.eval(r_in, rec, scattered)
+            + t * self.second.eval(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        let t = self.factor.at(rec);
+        (1.0 - t) * self.first.scattering_pdf(r_in, rec, scattered)
+            + t * self.second.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn emitted(&self, r_in: Ray, rec: &HitRecord<'_>) -> Color {
+        let t = self.factor.at(rec);
+        (1.0 - t) * self.first.emitted(r_in, rec) + t * self.second.emitted(r_in, rec)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.first.is_dispersive() || self.second.is_dispersive()
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        // 物体内部只能有一种介质，使用能够透射的那种材质的介质，两种都能透射时以第一种为准
+        self.first.medium().or_else(|| self.second.medium())
+    }
+}
+
+/// 带有法线贴图或凹凸贴图的材质
+///
+/// 在碰撞点用贴图扰动法线后再交给内部的材质计算散射，几何法线仍然决定 `front_face`。
+/// 着色法线与几何法线不一致时，落在两者之间的散射方向会被丢弃，避免光线穿过不透明表面。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::Color;
+/// use ray_tracing_in_one_weekend::material::{Lambertian, NormalMapped};
+/// use ray_tracing_in_one_weekend::normal_map::NormalMap;
+///
+/// // 程序化的波纹凹凸
+/// let ripples = NormalMap::Bump(Box::new(|u, v| 0.01 * (40.0 * u).sin() * (20.0 * v).sin()));
+/// let material = NormalMapped::new(Lambertian::new(Color::new(0.7, 0.7, 0.7)), ripples);
+/// ```
+pub struct NormalMapped<M: Material> {
+    /// 使用扰动后法线的材质
+    pub base: M,
+    /// 扰动法线的贴图
+    pub map: NormalMap,
+}
+
+impl<M: Material> NormalMapped<M> {
+    /// 创建一个新的法线贴图材质实例。
+    pub fn new(base: M, map: NormalMap) -> Self {
+        Self { base, map }
+    }
+
+    /// 把碰撞记录的法线替换为着色法线，入射光线位于着色法线背面时保留几何法线。
+    fn shade<'a>(&self, r_in: Ray, rec: &HitRecord<'a>) -> HitRecord<'a> {
+        let normal = self.map.shading_normal(rec);
+        let normal = if normal.dot(r_in.direction) < 0.0 {
+            normal
+        } else {
+            rec.normal
+        };
+        HitRecord { normal, ..*rec }
+    }
+
+    /// 散射方向相对于着色法线和几何法线是否位于表面的同一侧。
+    fn consistent(rec: &HitRecord<'_>, shaded: &HitRecord<'_>, scattered: Ray) -> bool {
+        let d = scattered.direction;
+        (d.dot(rec.normal) > 0.0) == (d.dot(shaded.normal) > 0.0)
+    }
+}
+
+impl<M: Material> Material for NormalMapped<M> {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        let shaded = self.shade(r_in, rec);
+        self.base
+            .scatter(r_in, &shaded)
+            .filter(|srec| Self::consistent(rec, &shaded, srec.scattered))
+    }
+
+    fn eval(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> Color {
+        let shaded = self.shade(r_in, rec);
+        if !Self::consistent(rec, &shaded, scattered) {
+            return Color::zero();
+        }
+        self.base.eval(r_in, &shaded, scattered)
+    }
+
+    fn scattering_pdf(&self, r_in: Ray, rec: &HitRecord<'_>, scattered: Ray) -> f64 {
+        let shaded = self.shade(r_in, rec);
+        if !Self::consistent(rec, &shaded, scattered) {
+            return 0.0;
+        }
+        self.base.scattering_pdf(r_in, &shaded, scattered)
+    }
+
+    fn emitted(&self, r_in: Ray, rec: &HitRecord<'_>) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+
+    fn medium(&self) -> Option<Medium> {
+        self.base.medium()
+    }
+}
+
+/// 次表面散射材质
+///
+/// 光线在表面发生菲涅尔反射，或者进入物体内部，在封闭的边界内做体积随机游走，
+/// 最后从表面上的另一个位置以余弦分布离开，可以渲染皮肤、蜡、大理石和牛奶等半透明物体。
+/// 离开物体的位置由被击中物体的 [`Hittable::hit`](crate::hittable::Hittable::hit) 求得，因此物体必须是封闭的。
+/// 碰撞记录中没有设置被击中的物体（[`HitRecord::object`]）时，进入物体的光线会被吸收。
+///
+/// 散射结果的起点不在碰撞点上，无法与光源采样结合，按照镜面散射处理。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::hittable::{HitRecord, Hittable};
+/// use ray_tracing_in_one_weekend::interval::Interval;
+/// use ray_tracing_in_one_weekend::material::{Material, Subsurface};
+/// use ray_tracing_in_one_weekend::sphere::Sphere;
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// // 红光在皮肤中传播得最远
+/// let skin = Subsurface::new(Color::new(0.85, 0.6, 0.5), Color::new(0.4, 0.15, 0.1), 1.4);
+///
+/// // 平均自由程远小于物体时，离开表面的能量接近设定的反照率，且不超过入射的能量
+/// let albedo = Color::new(0.85, 0.6, 0.5);
+/// let wax = Rc::new(Subsurface::new(albedo, Color::new(0.01, 0.01, 0.01), 1.4));
+/// let sphere = Sphere::new(Point3::zero(), 1.0, wax.clone());
+/// let r_in = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+/// let rec = sphere.hit(r_in, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// let n = 2000;
+/// let mut reflected = Color::zero();
+/// for _ in 0..n {
+///     if let Some(srec) = wax.scatter(r_in, &rec) {
+///         reflected += srec.attenuation;
+///     }
+/// }
+/// let reflected = reflected / n as f64;
+/// assert!(reflected.x < 1.0 && reflected.x > reflected.y && reflected.y > reflected.z);
+/// assert!((reflected - albedo).length() < 0.2, "{reflected:?}");
+///
+/// // 没有设置被击中的物体时，只剩下表面的镜面反射
+/// let rec = HitRecord::new(Point3::new(0.0, 1.0, 0.0), Vec3::unit_y(), 4.0, r_in, &skin);
+/// for _ in 0..100 {
+///     if let Some(srec) = skin.scatter(r_in, &rec) {
+///         assert!(srec.is_specular);
+///         assert!((srec.scattered.direction.unit_vector() - Vec3::unit_y()).length() < 1e-9);
+///     }
+/// }
+/// ```
+pub struct Subsurface {
+    /// 表面呈现的颜色，即多次散射后的反照率
+    pub albedo: Color,
+    /// 每个通道的平均自由程，即光线在两次散射之间平均传播的距离
+    pub mean_free_path: Color,
+    /// 表面的折射率，决定表面菲涅尔反射的强度
+    pub refraction_index: f64,
+}
+
+impl Subsurface {
+    /// 随机游走的最大散射次数
+    const MAX_BOUNCES: usize = 256;
+    /// 从这次散射开始使用俄罗斯轮盘赌终止随机游走
+    const RUSSIAN_ROULETTE_BOUNCES: usize = 16;
+
+    /// 创建一个新的次表面散射材质实例。
+    pub fn new(albedo: Color, mean_free_path: Color, refraction_index: f64) -> Self {
+        Self {
+            albedo,
+            mean_free_path,
+            refraction_index,
+        }
+    }
+
+    /// 由表面反照率反推介质的单次散射反照率（van de Hulst 近似）。
+    fn single_scattering_albedo(&self) -> Color {
+        let channel = |a: f64| {
+            let a = a.clamp(0.0, 1.0);
+            1.0 - (4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).sqrt()).powi(2)
+        };
+        Color::new(
+            channel(self.albedo.x),
+            channel(self.albedo.y),
+            channel(self.albedo.z),
+        )
+    }
+
+    /// 介质的消光系数，即平均自由程的倒数。
+    fn extinction(&self) -> Color {
+        let channel = |mfp: f64| 1.0 / mfp.max(1e-8);
+        Color::new(
+            channel(self.mean_free_path.x),
+            channel(self.mean_free_path.y),
+            channel(self.mean_free_path.z),
+        )
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, r_in: Ray, rec: &HitRecord<'_>) -> Option<ScatterRecord> {
+        // 从物体内部击中表面时不散射，随机游走只从外部进入物体
+        if !rec.front_face {
+            return None;
+        }
+
+        let reflect_probability =
+            Dielectric::new(self.refraction_index).reflect_probability(r_in, rec);
+        if random_double() < reflect_probability {
+            let reflected = r_in.direction.unit_vector().reflect(rec.normal);
+            return Some(ScatterRecord::specular(
+                Color::one(),
+                Ray::new(rec.p, reflected),
+            ));
+        }
+
+        let object = rec.object?;
+        let sigma_t = self.extinction();
+        let albedo = self.single_scattering_albedo();
+        let transmittance = |distance: f64| {
+            Color::new(
+                (-sigma_t.x * distance).exp(),
+                (-sigma_t.y * distance).exp(),
+                (-sigma_t.z * distance).exp(),
+            )
+        };
+        let average = |c: Color| (c.x + c.y + c.z) / 3.0;
+
+        let mut throughput = Color::one();
+        let mut p = rec.p;
+        // 以余弦分布进入物体
+        let direction = -rec.normal + Vec3::random_unit();
+        let mut direction = if direction.near_zero() {
+            -rec.normal
+        } else {
+            direction.unit_vector()
+        };
+
+        for bounce in 0..Self::MAX_BOUNCES {
+            // 随机选择一个通道采样自由程，概率密度为三个通道的平均值
+            let sigma = match (3.0 * random_double()) as usize {
+                0 => sigma_t.x,
+                1 => sigma_t.y,
+                _ => sigma_t.z,
+            };
+            let distance = -(1.0 - random_double()).ln() / sigma;
+
+            let ray = Ray::new(p, direction);
+            let exit = object.hit(ray, Interval::new(0.001, f64::INFINITY))?;
+            if exit.t <= distance {
+                // 到达边界，离开物体，碰撞记录中的法线朝向物体内部
+                let tr = transmittance(exit.t);
+                throughput = throughput * tr / average(tr);
+                let outward = -exit.normal;
+                let scattered = outward + Vec3::random_unit();
+                let scattered = if scattered.near_zero() {
+                    outward
+                } else {
+                    scattered
+                };
+                return Some(ScatterRecord::specular(
+                    throughput,
+                    Ray::new(exit.p, scattered),
+                ));
+            }
+
+            // 在介质中发生散射，各向同性地选择新的方向
+            let tr = transmittance(distance);
+            throughput = throughput * albedo * sigma_t * tr / average(sigma_t * tr);
+            p = ray.at(distance);
+            direction = Vec3::random_unit();
+
+            if bounce + 1 >= Self::RUSSIAN_ROULETTE_BOUNCES {
+                let survival = throughput.x.max(throughput.y).max(throughput.z).min(1.0);
+                if random_double() >= survival {
+                    return None;
+                }
+                throughput /= survival;
+            }
+        }
+        None
+    }
+}
+
+/// 漫反射光源材质
+///
+/// 只在正面向外发光，不会散射任何光线。
+pub struct DiffuseLight {
+    pub emit: Color,
+}
+
+impl DiffuseLight {
+    /// 创建一个新的漫反射光源材质实例。
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _: Ray, _: &HitRecord<'_>) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, _: Ray, rec: &HitRecord<'_>) -> Color {
+        if rec.front_face {
+            self.emit
+        } else {
+            Color::zero()
+        }
     }
 }
@@ -0,0 +1,261 @@
+//! 物体内部的介质，以及追踪路径当前所在介质的栈。
+
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+
+/// 由封闭表面包围的均匀介质
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Medium {
+    /// 介质的折射率
+    pub refraction_index: f64,
+    /// 吸收系数，光线在介质中每传播单位距离衰减为原来的 `exp(-absorption)`
+    pub absorption: Color,
+    /// 优先级，多个介质重叠时，重叠部分属于优先级最高的介质
+    pub priority: i32,
+}
+
+impl Medium {
+    /// 光线在介质中传播 `distance` 距离后的透射率（Beer–Lambert 定律）。
+    pub fn transmittance(&self, distance: f64) -> Color {
+        let channel = |sigma: f64| {
+            if sigma <= 0.0 {
+                1.0
+            } else {
+                (-sigma * distance).exp()
+            }
+        };
+        Color::new(
+            channel(self.absorption.x),
+            channel(self.absorption.y),
+            channel(self.absorption.z),
+        )
+    }
+}
+
+/// 路径当前所在的介质栈
+///
+/// 路径穿过定义了 [`Material::medium`] 的表面进入物体时压入该物体的介质，离开时弹出。
+/// 路径当前所在的介质是栈中优先级最高的介质，优先级相同时取最后进入的一个。
+/// 相机默认位于所有物体之外。
+///
+/// 位于更高优先级介质内部的表面是虚假的界面（例如玻璃杯壁内部的水面），
+/// 光线会直接穿过这类表面，不会发生散射。
+///
+/// 栈中的介质以材质的地址区分属于哪个物体：离开物体时弹出的是最后一个同一材质的条目。
+/// 因此两个互相重叠的物体不能共用同一个 `Rc<Dielectric>`，否则离开其中一个时可能弹出另一个的介质，
+/// 需要为每个物体分别创建材质实例。
+///
+/// # 示例
+/// ```
+/// use std::rc::Rc;
+/// use ray_tracing_in_one_weekend::hittable::Hittable;
+/// use ray_tracing_in_one_weekend::material::Dielectric;
+/// use ray_tracing_in_one_weekend::medium::MediumStack;
+/// use ray_tracing_in_one_weekend::sphere::Sphere;
+/// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+///
+/// // 光线沿直径穿过半径为 1 的玻璃球，在内部传播的距离为 2
+/// let glass = Dielectric::new(1.5).absorption(Color::new(0.5, 1.0, 0.0));
+/// let world = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Rc::new(glass));
+/// let world: &dyn Hittable = &world;
+///
+/// let mut media = MediumStack::new();
+/// let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+/// let (rec, transmittance) = media.hit(world, r);
+/// let rec = rec.unwrap();
+/// assert_eq!(transmittance, Color::one());
+/// assert_eq!(rec.exterior_ior, 1.0);
+///
+/// // 折射进入玻璃球后，到达另一侧的透射率满足 Beer–Lambert 定律
+/// let inside = Ray::new(rec.p, r.direction);
+/// media.update(&rec, inside);
+/// let (rec, transmittance) = media.hit(world, inside);
+/// assert!((rec.unwrap().p.x - 1.0).abs() < 1e-9);
+/// assert!((transmittance.x - (-1.0f64).exp()).abs() < 1e-9);
+/// assert!((transmittance.y - (-2.0f64).exp()).abs() < 1e-9);
+/// assert_eq!(transmittance.z, 1.0);
+/// ```
+#[derive(Clone, Default)]
+pub struct MediumStack<'a> {
+    entries: Vec<(&'a dyn Material, Medium)>,
+}
+
+impl<'a> MediumStack<'a> {
+    /// 连续穿过虚假界面的最大次数，避免重合的表面导致死循环
+    const MAX_FALSE_INTERFACES: usize = 64;
+
+    /// 创建一个新的空介质栈实例。
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// 路径当前所在的介质，位于所有物体之外时返回 None。
+    pub fn current(&self) -> Option<&Medium> {
+        self.current_excluding(None)
+    }
+
+    /// 忽略材质 `excluded` 时路径所在的介质。
+    fn current_excluding(&self, excluded: Option<&dyn Material>) -> Option<&Medium> {
+        self.entries
+            .iter()
+            .filter(|(mat, _)| excluded.is_none_or(|excluded| !std::ptr::addr_eq(*mat, excluded)))
+            .max_by_key(|(_, medium)| medium.priority)
+            .map(|(_, medium)| medium)
+    }
+
+    /// 在当前介质中传播 `distance` 距离后的透射率。
+    pub fn transmittance(&self, distance: f64) -> Color {
+        self.current()
+            .map_or(Color::one(), |medium| medium.transmittance(distance))
+    }
+
+    /// 光线 `r` 传播到碰撞点 `rec` 的这一段的透射率。
+    pub fn segment_transmittance(&self, r: Ray, rec: &HitRecord<'_>) -> Color {
+        self.transmittance(rec.t * r.direction.length())
+    }
+
+    /// 求光线与场景中下一个真实界面的交点，跳过沿途的虚假界面。
+    ///
+    /// 命中有内部介质的表面时，会在碰撞记录中填写物体外侧介质的折射率。
+    ///
+    /// # 返回值
+    ///
+    /// 碰撞记录（没有命中时为 None）以及光线在介质中传播到该点（或无穷远处）的透射率。
+    ///
+    /// # 示例
+    /// ```
+    /// use std::rc::Rc;
+    /// use ray_tracing_in_one_weekend::hittable_list::HittableList;
+    /// use ray_tracing_in_one_weekend::material::Dielectric;
+    /// use ray_tracing_in_one_weekend::medium::MediumStack;
+    /// use ray_tracing_in_one_weekend::sphere::Sphere;
+    /// use ray_tracing_in_one_weekend::{Color, Point3, Ray, Vec3};
+    ///
+    /// // 高优先级的玻璃球与低优先级的水重叠，重叠部分属于玻璃
+    /// let glass = Dielectric::new(1.5).absorption(Color::new(0.5, 0.5, 0.5)).priority(2);
+    /// let water = Dielectric::new(1.33).absorption(Color::new(3.0, 3.0, 3.0)).priority(1);
+    /// let mut world = HittableList::new();
+    /// world.add(Box::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Rc::new(glass))));
+    /// world.add(Box::new(Sphere::new(Point3::new(1.5, 0.0, 0.0), 1.0, Rc::new(water))));
+    ///
+    /// let mut media = MediumStack::new();
+    /// let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    /// let (rec, _) = media.hit(&world, r);
+    /// let rec = rec.unwrap();
+    /// let inside = Ray::new(rec.p, r.direction);
+    /// media.update(&rec, inside);
+    ///
+    /// // 玻璃内部 x = 0.5 处的水面是虚假界面，光线沿原方向穿过，停在 x = 1 处玻璃与水的界面上
+    /// let (rec, transmittance) = media.hit(&world, inside);
+    /// let rec = rec.unwrap();
+    /// assert!((rec.p - Point3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+    /// assert!(!rec.front_face);
+    /// assert_eq!(rec.exterior_ior, 1.33);
+    /// // 整段路程都在玻璃中，只有玻璃的吸收
+    /// assert!((transmittance.x - (-1.0f64).exp()).abs() < 1e-9);
+    /// ```
+    pub fn hit(&mut self, world: &'a dyn Hittable, r: Ray) -> (Option<HitRecord<'a>>, Color) {
+        let mut transmittance = Color::one();
+        let mut ray = r;
+
+        for _ in 0..Self::MAX_FALSE_INTERFACES {
+            let Some(mut rec) = world.hit(ray, Interval::new(0.001, f64::INFINITY)) else {
+                return (None, transmittance * self.transmittance(f64::INFINITY));
+            };
+            transmittance = transmittance * self.segment_transmittance(ray, &rec);
+
+            let Some(medium) = rec.mat.medium() else {
+                return (Some(rec), transmittance);
+            };
+            let exterior = self.exterior(&rec);
+
+            if exterior.is_some_and(|exterior| exterior.priority > medium.priority) {
+                // 虚假界面：只更新所在的介质，光线沿原方向继续传播
+                self.cross(&rec, medium);
+                ray = Ray::new(rec.p, ray.direction).with_wavelength(ray.wavelength);
+                continue;
+            }
+
+            rec.exterior_ior = exterior.map_or(1.0, |exterior| exterior.refraction_index);
+            return (Some(rec), transmittance);
+        }
+
+        (None, Color::zero())
+    }
+
+    /// 光线 `r` 在 `(0, t_max)` 范围内的透射率，用于检查两点之间是否可见。
+    ///
+    /// 途中的虚假界面只改变所在的介质，被真实界面遮挡时返回 None。介质栈本身不会被修改。
+    pub fn shadow_transmittance(
+        &self,
+        world: &'a dyn Hittable,
+        r: Ray,
+        t_max: f64,
+    ) -> Option<Color> {
+        let mut media = self.clone();
+        let mut transmittance = Color::one();
+        let mut ray = r;
+        let mut t_max = t_max;
+
+        for _ in 0..Self::MAX_FALSE_INTERFACES {
+            let Some(rec) = world.hit(ray, Interval::new(0.001, t_max - 0.001)) else {
+                return Some(transmittance * media.transmittance(t_max * ray.direction.length()));
+            };
+            let medium = rec.mat.medium()?;
+            // 真实界面会遮挡光线，虚假界面则直接穿过
+            if media
+                .exterior(&rec)
+                .is_none_or(|exterior| exterior.priority <= medium.priority)
+            {
+                return None;
+            }
+
+            transmittance = transmittance * media.segment_transmittance(ray, &rec);
+            media.cross(&rec, medium);
+            ray = Ray::new(rec.p, ray.direction).with_wavelength(ray.wavelength);
+            t_max -= rec.t;
+        }
+
+        None
+    }
+
+    /// 碰撞点所在表面外侧的介质。
+    fn exterior(&self, rec: &HitRecord<'_>) -> Option<Medium> {
+        if rec.front_face {
+            self.current().copied()
+        } else {
+            self.current_excluding(Some(rec.mat)).copied()
+        }
+    }
+
+    /// 根据碰撞点处的散射结果更新介质栈。
+    ///
+    /// 散射方向穿过表面时，从正面穿过表示进入物体，从背面穿过表示离开物体。
+    pub fn update(&mut self, rec: &HitRecord<'a>, scattered: Ray) {
+        let Some(medium) = rec.mat.medium() else {
+            return;
+        };
+        // 碰撞记录中的法线总是朝向入射光线一侧
+        if scattered.direction.dot(rec.normal) < 0.0 {
+            self.cross(rec, medium);
+        }
+    }
+
+    /// 穿过碰撞点所在的表面，进入或离开该物体的介质。
+    fn cross(&mut self, rec: &HitRecord<'a>, medium: Medium) {
+        if rec.front_face {
+            self.entries.push((rec.mat, medium));
+        } else if let Some(index) = self
+            .entries
+            .iter()
+            .rposition(|(mat, _)| std::ptr::addr_eq(*mat, rec.mat))
+        {
+            self.entries.remove(index);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! 微表面模型，包含 GGX（Trowbridge-Reitz）法线分布以及导体的菲涅尔项。
+//!
+//! 这里的方向向量都位于以宏观表面法线为 z 轴的局部坐标系中。
+
+use crate::color::Color;
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+
+/// GGX（Trowbridge-Reitz）微表面法线分布，支持各向异性粗糙度。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrowbridgeReitz {
+    /// 沿局部坐标系 x 轴的粗糙度
+    pub alpha_x: f64,
+    /// 沿局部坐标系 y 轴的粗糙度
+    pub alpha_y: f64,
+}
+
+impl TrowbridgeReitz {
+    /// 粗糙度低于该值时把表面当作理想光滑表面处理
+    const SMOOTH_ALPHA: f64 = 1e-3;
+
+    /// 创建一个新的 GGX 法线分布实例。
+    pub fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Self {
+            alpha_x: alpha_x.max(0.0),
+            alpha_y: alpha_y.max(0.0),
+        }
+    }
+
+    /// 表面是否足够光滑，可以当作镜面处理。
+    #[inline]
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < Self::SMOOTH_ALPHA
+    }
+
+    /// 法线分布函数 D，即微表面法线为 `wm` 的微表面面积密度。
+    pub fn d(&self, wm: Vec3) -> f64 {
+        if wm.z <= 0.0 {
+            return 0.0;
+        }
+        let x = wm.x / self.alpha_x;
+        let y = wm.y / self.alpha_y;
+        let t = x * x + y * y + wm.z * wm.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * t * t)
+    }
+
+    /// Smith 遮蔽函数中的辅助函数 Λ。
+    pub fn lambda(&self, w: Vec3) -> f64 {
+        let cos2_theta = w.z * w.z;
+        if cos2_theta <= 0.0 {
+            return 0.0;
+        }
+        // α² tan²θ，其中 α 按照方向的方位角在 alpha_x 与 alpha_y 之间插值
+        let alpha2_tan2_theta =
+            ((w.x * self.alpha_x).powi(2) + (w.y * self.alpha_y).powi(2)) / cos2_theta;
+        ((1.0 + alpha2_tan2_theta).sqrt() - 1.0) / 2.0
+    }
+
+    /// 单方向的 Smith 遮蔽函数 G1。
+    #[inline]
+    pub fn g1(&self, w: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// 高度相关的 Smith 遮蔽-阴影函数 G。
+    #[inline]
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// 从方向 `wo` 可见的微表面法线分布 `D_wo(wm) = G1(wo) * max(0, wo·wm) * D(wm) / cosθo`。
+    pub fn d_visible(&self, wo: Vec3, wm: Vec3) -> f64 {
+        if wo.z <= 0.0 {
+            return 0.0;
+        }
+        self.g1(wo) * wo.dot(wm).max(0.0) * self.d(wm) / wo.z
+    }
+
+    /// 按照从 `wo` 可见的法线分布采样一个微表面法线（Heitz 2018）。
+    pub fn sample_visible_normal(&self, wo: Vec3) -> Vec3 {
+        // 把观察方向拉伸到粗糙度为 1 的半球配置
+        let vh = Vec3::new(self.alpha_x * wo.x, self.alpha_y * wo.y, wo.z).unit_vector();
+
+        // 以观察方向为轴建立正交基
+        let len_squared = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if len_squared > 0.0 {
+            Vec3::new(-vh.y, vh.x, 0.0) / len_squared.sqrt()
+        } else {
+            Vec3::unit_x()
+        };
+        let t2 = Vec3::cross(vh, t1);
+
+        // 在投影后的半圆盘上均匀采样
+        let r = random_double().sqrt();
+        let phi = 2.0 * PI * random_double();
+        let p1 = r * phi.cos();
+        let p2 = r * phi.sin();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * p2;
+
+        // 投影回半球，再变换回原来的粗糙度
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+        Vec3::new(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(1e-6)).unit_vector()
+    }
+}
+
+/// 计算导体表面的菲涅尔反射率。
+///
+/// # 参数
+///
+/// * `cos_theta` - 入射方向与（微）表面法线夹角的余弦。
+/// * `eta` - 复折射率的实部。
+/// * `k` - 复折射率的虚部，即消光系数。
+pub fn fresnel_conductor(cos_theta: f64, eta: Color, k: Color) -> Color {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_theta.clamp(0.0, 1.0).powi(2);
+        let sin2 = 1.0 - cos2;
+        let eta2 = eta * eta;
+        let k2 = k * k;
+
+        let t0 = eta2 - k2 - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t2 = 2.0 * cos_theta.clamp(0.0, 1.0) * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        0.5 * (rp + rs)
+    };
+    Color::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
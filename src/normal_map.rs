@@ -0,0 +1,75 @@
+//! 法线贴图与凹凸贴图，用于在不增加几何体的情况下为表面添加细节。
+
+use crate::hittable::HitRecord;
+use crate::image::Image;
+use crate::onb::Onb;
+use crate::vec3::Vec3;
+
+/// 扰动着色法线的方式
+///
+/// 贴图都以碰撞点的表面坐标 (u, v) 为参数，可以由图像或者程序化的函数给出。
+pub enum NormalMap {
+    /// 切线空间法线贴图：x、y、z 分量分别对应 `∂p/∂u`、与之垂直的副切线以及几何法线方向
+    Tangent(Box<dyn Fn(f64, f64) -> Vec3>),
+    /// 标量凹凸贴图：表面沿法线方向的位移，单位与场景相同
+    Bump(Box<dyn Fn(f64, f64) -> f64>),
+}
+
+impl NormalMap {
+    /// 计算凹凸贴图导数时使用的表面坐标步长
+    const BUMP_DELTA: f64 = 1e-4;
+
+    /// 由切线空间法线贴图图像创建，颜色分量 [0, 1] 映射到法线分量 [-1, 1]。
+    pub fn from_image(image: Image) -> Self {
+        Self::Tangent(Box::new(move |u, v| 2.0 * image.sample(u, v) - Vec3::one()))
+    }
+
+    /// 由灰度高度图创建凹凸贴图，颜色分量的平均值乘以 `scale` 得到位移。
+    pub fn bump_from_image(image: Image, scale: f64) -> Self {
+        Self::Bump(Box::new(move |u, v| {
+            let c = image.sample(u, v);
+            scale * (c.x + c.y + c.z) / 3.0
+        }))
+    }
+
+    /// 计算碰撞点处扰动后的着色法线，结果与 `rec.normal` 位于表面的同一侧。
+    pub fn shading_normal(&self, rec: &HitRecord<'_>) -> Vec3 {
+        // 物体朝外的几何法线
+        let outward = if rec.front_face {
+            rec.normal
+        } else {
+            -rec.normal
+        };
+
+        let shading = match self {
+            Self::Tangent(map) => {
+                // 以几何法线为 z 轴，把 ∂p/∂u 正交化后作为 x 轴
+                let tangent = rec.dpdu - outward * outward.dot(rec.dpdu);
+                let tangent = if tangent.near_zero() {
+                    Onb::new(outward).u()
+                } else {
+                    tangent.unit_vector()
+                };
+                let bitangent = Vec3::cross(outward, tangent);
+                let n = map(rec.u, rec.v);
+                n.x * tangent + n.y * bitangent + n.z * outward
+            }
+            Self::Bump(height) => {
+                // 沿法线移动表面后，新的切线为 ∂p/∂u + ∂h/∂u * n，着色法线为两条切线的叉积
+                let d = Self::BUMP_DELTA;
+                let dhdu = (height(rec.u + d, rec.v) - height(rec.u - d, rec.v)) / (2.0 * d);
+                let dhdv = (height(rec.u, rec.v + d) - height(rec.u, rec.v - d)) / (2.0 * d);
+                let dpdu = rec.dpdu + dhdu * outward;
+                let dpdv = rec.dpdv + dhdv * outward;
+                let n = Vec3::cross(dpdu, dpdv);
+                if n.dot(outward) < 0.0 { -n } else { n }
+            }
+        };
+
+        if shading.near_zero() {
+            return rec.normal;
+        }
+        let shading = shading.unit_vector();
+        if rec.front_face { shading } else { -shading }
+    }
+}
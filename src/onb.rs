@@ -0,0 +1,82 @@
+//! 正交基（Orthonormal Basis）的定义，用于在局部坐标系和世界坐标系之间转换向量。
+
+use crate::vec3::Vec3;
+
+/// 由三个互相正交的单位向量组成的坐标系，其中 w 轴与给定的法线方向一致。
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    /// 以给定的法线向量为 w 轴创建一个正交基。
+    pub fn new(n: Vec3) -> Self {
+        let w = n.unit_vector();
+        // 选择一个与 w 不平行的辅助向量
+        let a = if w.x.abs() > 0.9 {
+            Vec3::unit_y()
+        } else {
+            Vec3::unit_x()
+        };
+        let v = Vec3::cross(w, a).unit_vector();
+        let u = Vec3::cross(w, v);
+
+        Self { axis: [u, v, w] }
+    }
+
+    /// 以给定的法线向量为 w 轴、切线 `t` 在垂直于法线的平面上的投影为 u 轴创建一个正交基。
+    ///
+    /// 切线与法线平行（或为零向量）时退化为 [`Onb::new`]。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::onb::Onb;
+    /// use ray_tracing_in_one_weekend::vec3::Vec3;
+    ///
+    /// let onb = Onb::from_tangent(Vec3::unit_z(), Vec3::new(1.0, 0.0, 1.0));
+    /// assert!((onb.u() - Vec3::unit_x()).near_zero());
+    /// assert!((onb.v() - Vec3::unit_y()).near_zero());
+    /// ```
+    pub fn from_tangent(n: Vec3, t: Vec3) -> Self {
+        let w = n.unit_vector();
+        let u = t - w * w.dot(t);
+        if u.near_zero() {
+            return Self::new(n);
+        }
+        let u = u.unit_vector();
+        let v = Vec3::cross(w, u);
+
+        Self { axis: [u, v, w] }
+    }
+
+    #[inline]
+    pub fn u(&self) -> Vec3 {
+        self.axis[0]
+    }
+
+    #[inline]
+    pub fn v(&self) -> Vec3 {
+        self.axis[1]
+    }
+
+    #[inline]
+    pub fn w(&self) -> Vec3 {
+        self.axis[2]
+    }
+
+    /// 将局部坐标系下的向量转换到世界坐标系。
+    #[inline]
+    pub fn transform(&self, v: Vec3) -> Vec3 {
+        v.x * self.axis[0] + v.y * self.axis[1] + v.z * self.axis[2]
+    }
+
+    /// 将世界坐标系下的向量转换到局部坐标系。
+    #[inline]
+    pub fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            v.dot(self.axis[0]),
+            v.dot(self.axis[1]),
+            v.dot(self.axis[2]),
+        )
+    }
+}
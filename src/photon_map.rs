@@ -0,0 +1,395 @@
+//! 光子映射积分器，以及保存光子使用的 kd 树。
+//!
+//! 先从光源发射光子并在场景中追踪，把光子保存在漫反射等非镜面表面上，
+//! 再在相机射线命中非镜面表面时，根据附近的光子密度估计间接光照与焦散。
+
+use crate::color::Color;
+use crate::hittable::Hittable;
+use crate::integrator::{Integrator, sample_light};
+use crate::light::LightList;
+use crate::medium::MediumStack;
+use crate::ray::{Point3, Ray};
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+
+/// 保存在表面上的光子
+#[derive(Debug, Copy, Clone)]
+pub struct Photon {
+    /// 光子所在的位置
+    pub p: Point3,
+    /// 光子到达该位置时的传播方向（单位向量）
+    pub direction: Vec3,
+    /// 光子携带的能量
+    pub power: Color,
+}
+
+/// 用于按位置查找光子的 kd 树
+///
+/// 树以隐式平衡二叉树的形式保存在数组中：每个区间的中点是该子树的根节点，
+/// 左右两半分别是它的左右子树。
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    /// 每个节点的划分轴，0、1、2 分别对应 x、y、z
+    axes: Vec<u8>,
+}
+
+impl PhotonMap {
+    /// 由一组光子构建 kd 树。
+    pub fn new(photons: Vec<Photon>) -> Self {
+        let mut map = Self {
+            axes: vec![0; photons.len()],
+            photons,
+        };
+        let len = map.photons.len();
+        map.build(0, len);
+        map
+    }
+
+    /// 光子的数量
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// 是否没有任何光子
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    #[inline]
+    fn coordinate(p: Point3, axis: u8) -> f64 {
+        match axis {
+            0 => p.x,
+            1 => p.y,
+            _ => p.z,
+        }
+    }
+
+    /// 递归构建区间 [lo, hi) 对应的子树，沿包围盒最长的轴在中位数处划分。
+    fn build(&mut self, lo: usize, hi: usize) {
+        if hi - lo <= 1 {
+            return;
+        }
+
+        let (min, max) = self.photons[lo..hi].iter().fold(
+            (
+                Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+                Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), photon| {
+                (
+                    Vec3::new(
+                        min.x.min(photon.p.x),
+                        min.y.min(photon.p.y),
+                        min.z.min(photon.p.z),
+                    ),
+                    Vec3::new(
+                        max.x.max(photon.p.x),
+                        max.y.max(photon.p.y),
+                        max.z.max(photon.p.z),
+                    ),
+                )
+            },
+        );
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+
+        let mid = (lo + hi) / 2;
+        self.photons[lo..hi].select_nth_unstable_by(mid - lo, |a, b| {
+            Self::coordinate(a.p, axis).total_cmp(&Self::coordinate(b.p, axis))
+        });
+        self.axes[mid] = axis;
+
+        self.build(lo, mid);
+        self.build(mid + 1, hi);
+    }
+
+    /// 对与 `p` 距离不超过 `radius` 的每一个光子调用 `f`。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::photon_map::{Photon, PhotonMap};
+    /// use ray_tracing_in_one_weekend::utils::random_double_range;
+    /// use ray_tracing_in_one_weekend::{Color, Point3, Vec3};
+    ///
+    /// let random_point = || Vec3::random_range(-1.0, 1.0);
+    /// // 用光子的能量记录它的序号
+    /// let photons: Vec<Photon> = (0..500)
+    ///     .map(|i| Photon {
+    ///         p: random_point(),
+    ///         direction: Vec3::unit_y(),
+    ///         power: Color::new(i as f64, 0.0, 0.0),
+    ///     })
+    ///     .collect();
+    /// let map = PhotonMap::new(photons.clone());
+    ///
+    /// // 与逐个检查所有光子的结果一致
+    /// for _ in 0..50 {
+    ///     let p: Point3 = random_point();
+    ///     let radius = random_double_range(0.05, 0.8);
+    ///     let mut found = Vec::new();
+    ///     map.for_each_within(p, radius, |photon| found.push(photon.power.x as usize));
+    ///     found.sort();
+    ///
+    ///     let expected: Vec<usize> = (0..photons.len())
+    ///         .filter(|&i| (photons[i].p - p).length() <= radius)
+    ///         .collect();
+    ///     assert_eq!(found, expected);
+    /// }
+    /// ```
+    pub fn for_each_within<F: FnMut(&Photon)>(&self, p: Point3, radius: f64, mut f: F) {
+        self.search(0, self.photons.len(), p, radius * radius, &mut f);
+    }
+
+    fn search<F: FnMut(&Photon)>(
+        &self,
+        lo: usize,
+        hi: usize,
+        p: Point3,
+        radius_squared: f64,
+        f: &mut F,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = (lo + hi) / 2;
+        let photon = &self.photons[mid];
+        if (photon.p - p).length_squared() <= radius_squared {
+            f(photon);
+        }
+
+        // 先搜索查询点所在的一侧，另一侧只有在划分平面落在搜索半径内时才需要搜索
+        let axis = self.axes[mid];
+        let delta = Self::coordinate(p, axis) - Self::coordinate(photon.p, axis);
+        let (near, far) = if delta < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search(near.0, near.1, p, radius_squared, f);
+        if delta * delta <= radius_squared {
+            self.search(far.0, far.1, p, radius_squared, f);
+        }
+    }
+}
+
+/// 当前一轮渲染使用的光子图以及搜索半径
+struct PhotonPass {
+    map: Option<PhotonMap>,
+    radius: f64,
+}
+
+/// 光子映射积分器
+///
+/// 直接光照通过对光源采样计算，焦散和间接光照则由光子图的密度估计得到。
+/// 光子只能从 [`LightList`] 中的光源发出，背景的光照不会被光子图考虑。
+///
+/// 启用渐进式光子映射后，每一轮采样都会重新发射光子，并按照 `alpha` 逐渐缩小搜索半径，
+/// 最终结果是各轮结果的平均值，随着采样数增加收敛到正确的结果。
+pub struct PhotonMapper {
+    /// 每一轮发射的光子数量
+    pub photon_count: usize,
+    /// 初始的密度估计搜索半径
+    pub radius: f64,
+    /// 光子和相机射线的最大散射次数
+    pub max_depth: i32,
+    /// 渐进式光子映射中搜索半径的缩小参数，取值范围 (0, 1)，为 None 时不启用
+    pub alpha: Option<f64>,
+    pass: RefCell<PhotonPass>,
+}
+
+impl PhotonMapper {
+    /// 创建一个新的光子映射积分器实例。
+    pub fn new(photon_count: usize, radius: f64) -> Self {
+        Self {
+            photon_count,
+            radius,
+            max_depth: 10,
+            alpha: None,
+            pass: RefCell::new(PhotonPass { map: None, radius }),
+        }
+    }
+
+    /// 设置光子和相机射线的最大散射次数。
+    pub fn max_depth(mut self, max_depth: i32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// 启用渐进式光子映射，`alpha` 越小搜索半径缩小得越快，通常取 0.7 左右。
+    pub fn progressive(mut self, alpha: f64) -> Self {
+        self.alpha = Some(alpha.clamp(0.0, 1.0));
+        self
+    }
+
+    /// 当前一轮密度估计使用的搜索半径。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::hittable_list::HittableList;
+    /// use ray_tracing_in_one_weekend::integrator::Integrator;
+    /// use ray_tracing_in_one_weekend::light::LightList;
+    /// use ray_tracing_in_one_weekend::photon_map::PhotonMapper;
+    ///
+    /// let (world, lights) = (HittableList::new(), LightList::new());
+    /// let alpha = 0.7;
+    /// let mapper = PhotonMapper::new(0, 0.5).progressive(alpha);
+    ///
+    /// // 第 k 轮的半径是上一轮的 sqrt((k + α) / (k + 1)) 倍
+    /// mapper.begin_pass(0, &world, &lights);
+    /// assert_eq!(mapper.current_radius(), 0.5);
+    /// for pass in 1..10 {
+    ///     let previous = mapper.current_radius();
+    ///     mapper.begin_pass(pass, &world, &lights);
+    ///     let k = pass as f64;
+    ///     let expected = previous * ((k + alpha) / (k + 1.0)).sqrt();
+    ///     assert!((mapper.current_radius() - expected).abs() < 1e-12);
+    /// }
+    /// ```
+    pub fn current_radius(&self) -> f64 {
+        self.pass.borrow().radius
+    }
+
+    /// 从光源发射光子并追踪，返回保存在非镜面表面上的光子。
+    ///
+    /// 直接来自光源的光子不会被保存，这部分光照由光源采样负责。
+    fn trace_photons(&self, world: &dyn Hittable, lights: &LightList) -> Vec<Photon> {
+        let mut photons = Vec::new();
+        for _ in 0..self.photon_count {
+            let Some((light, pmf)) = lights.choose() else {
+                break;
+            };
+            let Some(emission) = light.sample_emission() else {
+                continue;
+            };
+            if emission.pdf_pos <= 0.0 || emission.pdf_dir <= 0.0 {
+                continue;
+            }
+
+            let cos_theta = emission
+                .normal
+                .map_or(1.0, |n| n.dot(emission.ray.direction).abs());
+            let mut power = emission.radiance * cos_theta
+                / (pmf * emission.pdf_pos * emission.pdf_dir * self.photon_count as f64);
+            let mut ray = emission.ray;
+            let mut media = MediumStack::new();
+
+            for depth in 0..self.max_depth {
+                let (hit, transmittance) = media.hit(world, ray);
+                let Some(rec) = hit else {
+                    break;
+                };
+                power = power * transmittance;
+
+                let srec = rec.mat.scatter(ray, &rec);
+                let is_specular = srec.as_ref().is_some_and(|srec| srec.is_specular);
+                if depth > 0 && !is_specular {
+                    photons.push(Photon {
+                        p: rec.p,
+                        direction: ray.direction.unit_vector(),
+                        power,
+                    });
+                }
+
+                let Some(srec) = srec else {
+                    break;
+                };
+
+                // 俄罗斯轮盘赌：按衰减决定光子是否继续传播，存活的光子补偿能量
+                let attenuation = srec.attenuation;
+                let survival = attenuation.x.max(attenuation.y).max(attenuation.z).min(1.0);
+                if random_double() >= survival {
+                    break;
+                }
+                media.update(&rec, srec.scattered);
+                power = power * attenuation / survival;
+                ray = srec.scattered;
+            }
+        }
+        photons
+    }
+}
+
+impl Integrator for PhotonMapper {
+    fn begin_pass(&self, pass: i32, world: &dyn Hittable, lights: &LightList) {
+        let mut state = self.pass.borrow_mut();
+        // 非渐进模式下只需要生成一次光子图
+        if self.alpha.is_none() && state.map.is_some() {
+            return;
+        }
+
+        // 概率渐进式光子映射：第 i 轮的半径平方为 r² * Π (k + α) / (k + 1)
+        if let Some(alpha) = self.alpha
+            && pass > 0
+        {
+            let k = pass as f64;
+            state.radius *= ((k + alpha) / (k + 1.0)).sqrt();
+        }
+        state.map = Some(PhotonMap::new(self.trace_photons(world, lights)));
+    }
+
+    fn ray_color(&self, r: Ray, world: &dyn Hittable, lights: &LightList) -> Color {
+        if self.pass.borrow().map.is_none() {
+            self.begin_pass(0, world, lights);
+        }
+        let state = self.pass.borrow();
+        let Some(map) = state.map.as_ref() else {
+            return Color::zero();
+        };
+
+        let mut color = Color::zero();
+        let mut throughput = Color::one();
+        let mut ray = r;
+        let mut media = MediumStack::new();
+
+        // 沿镜面散射方向一直追踪，直到命中非镜面表面
+        for _ in 0..self.max_depth {
+            let (hit, transmittance) = media.hit(world, ray);
+            throughput = throughput * transmittance;
+            let Some(rec) = hit else {
+                color += throughput * lights.background(ray);
+                break;
+            };
+
+            color += throughput * rec.mat.emitted(ray, &rec);
+
+            let Some(srec) = rec.mat.scatter(ray, &rec) else {
+                break;
+            };
+            if srec.is_specular {
+                media.update(&rec, srec.scattered);
+                throughput = throughput * srec.attenuation;
+                ray = srec.scattered;
+                continue;
+            }
+
+            // 直接光照
+            color += throughput * sample_light(ray, &rec, world, lights, None, &media);
+
+            // 间接光照与焦散：L = Σ f * Φ / (π r²)，其中 eval 返回的是 f * cosθ
+            let radius = state.radius;
+            let mut estimate = Color::zero();
+            map.for_each_within(rec.p, radius, |photon| {
+                let wi = -photon.direction;
+                let cos_theta = rec.normal.dot(wi);
+                if cos_theta <= 0.0 {
+                    return;
+                }
+                let f = rec.mat.eval(ray, &rec, Ray::new(rec.p, wi)) / cos_theta;
+                estimate += f * photon.power;
+            });
+            color += throughput * estimate / (PI * radius * radius);
+            break;
+        }
+
+        color
+    }
+}
@@ -12,13 +12,25 @@ pub type Point3 = Vec3;
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    /// 光谱渲染时射线携带的（主）波长，单位为纳米；RGB 渲染时为 None
+    pub wavelength: Option<f64>,
 }
 
 impl Ray {
     /// 创建一个新的光线实例。
     #[inline]
     pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Self { origin, direction }
+        Self {
+            origin,
+            direction,
+            wavelength: None,
+        }
+    }
+
+    /// 返回一条携带指定波长（单位为纳米）的相同射线。
+    #[inline]
+    pub fn with_wavelength(self, wavelength: Option<f64>) -> Self {
+        Self { wavelength, ..self }
     }
 
     /// 计算光线在 t 时刻到达的位置。
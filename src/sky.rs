@@ -0,0 +1,381 @@
+//! 基于 Preetham 模型的物理天空与太阳，用于不依赖 HDR 环境贴图的室外光照。
+
+use crate::color::Color;
+use crate::light::{EmissionSample, Light, LightSample};
+use crate::onb::Onb;
+use crate::ray::{Point3, Ray};
+use crate::spectrum::xyz_to_linear_srgb;
+use crate::utils::random_double;
+use crate::vec3::Vec3;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// 大气层外垂直入射的太阳照度，与天空亮度一样以千坎德拉每平方米（千勒克斯）为单位
+const SUN_ILLUMINANCE: f64 = 128.0;
+
+/// 太阳圆盘默认的角半径，单位为角度
+const SUN_ANGULAR_RADIUS: f64 = 0.2667;
+
+/// 计算地面接收到的天空辐照度时，天顶角和方位角方向的积分步数
+const IRRADIANCE_STEPS: (usize, usize) = (32, 64);
+
+/// 天空光源，使用 Preetham 解析天空模型，并带有一个可以被重要性采样的太阳圆盘。
+///
+/// 射线逃逸出场景时按照方向返回天空、太阳或者地面的辐亮度，取代默认的渐变背景；
+/// 作为光源被采样时只采样太阳圆盘，天空其余部分的光照由 BSDF 采样得到。
+/// 场景中 y 轴朝上，地平线以下是反照率为 `ground_albedo` 的漫反射地面。
+///
+/// 从光源出发追踪路径时（光子映射），只沿太阳圆盘内的方向发射光线，
+/// 光线从场景包围球外一个垂直于该方向的圆盘上发出，因此需要通过 [`SkyLight::scene_bounds`]
+/// 指定能包住整个场景的球体。双向路径追踪仍然只通过相机子路径和光源采样计入天空的光照。
+///
+/// # 示例
+/// ```
+/// use ray_tracing_in_one_weekend::light::{Light, LightList};
+/// use ray_tracing_in_one_weekend::sky::SkyLight;
+/// use ray_tracing_in_one_weekend::{Color, Point3, Vec3};
+///
+/// // 下午时分偏西的太阳，场景位于以原点为球心、半径为 20 的球内
+/// let sky = SkyLight::new(Vec3::new(-1.0, 0.8, 0.3), 3.0)
+///     .ground_albedo(Color::new(0.3, 0.25, 0.2))
+///     .scene_bounds(Point3::zero(), 20.0);
+///
+/// // 发出的阳光从包围球外射向场景
+/// let emission = sky.sample_emission().unwrap();
+/// assert!(emission.ray.origin.length() >= 20.0 - 1e-9);
+/// assert!(emission.ray.direction.y < 0.0);
+///
+/// let mut lights = LightList::new();
+/// lights.add(Box::new(sky));
+/// ```
+pub struct SkyLight {
+    /// 指向太阳的单位方向向量
+    sun_direction: Vec3,
+    /// 太阳的天顶角，地平线以下时截断为 90°
+    sun_theta: f64,
+    /// 太阳圆盘角半径的余弦值
+    sun_cos_radius: f64,
+    /// 经过大气衰减后，垂直于太阳方向的平面上的太阳辐照度
+    sun_irradiance: Color,
+    /// 亮度 Y 以及色度 x、y 的 Perez 分布系数
+    perez: [Perez; 3],
+    /// 天顶处的亮度 Y 以及色度 x、y
+    zenith: [f64; 3],
+    /// 水平地面接收到的天空辐照度，不包括太阳
+    sky_irradiance: Color,
+    /// 地面反照率
+    ground_albedo: Color,
+    /// 辐亮度的整体缩放
+    scale: f64,
+    /// 场景包围球的球心
+    scene_center: Point3,
+    /// 场景包围球的半径
+    scene_radius: f64,
+}
+
+impl SkyLight {
+    /// 创建一个新的天空光源实例。
+    ///
+    /// # 参数
+    ///
+    /// - `sun_direction`：指向太阳的方向，不需要是单位向量
+    /// - `turbidity`：大气浑浊度，晴朗的天空约为 2，雾霾天约为 10，会被截断到 [1.7, 10]
+    pub fn new(sun_direction: Vec3, turbidity: f64) -> Self {
+        let sun_direction = sun_direction.unit_vector();
+        let turbidity = turbidity.clamp(1.7, 10.0);
+        let sun_theta = sun_direction.y.clamp(0.0, 1.0).acos();
+
+        let mut sky = Self {
+            sun_direction,
+            sun_theta,
+            sun_cos_radius: SUN_ANGULAR_RADIUS.to_radians().cos(),
+            sun_irradiance: Self::sun_transmittance(sun_theta, turbidity) * SUN_ILLUMINANCE,
+            perez: Perez::coefficients(turbidity),
+            zenith: Self::zenith(sun_theta, turbidity),
+            sky_irradiance: Color::zero(),
+            ground_albedo: Color::new(0.3, 0.3, 0.3),
+            scale: 0.03,
+            scene_center: Point3::zero(),
+            scene_radius: 1000.0,
+        };
+        sky.sky_irradiance = sky.integrate_sky_irradiance();
+        sky
+    }
+
+    /// 设置地面反照率，默认为 0.3 的灰色。
+    pub fn ground_albedo(mut self, ground_albedo: Color) -> Self {
+        self.ground_albedo = ground_albedo;
+        self
+    }
+
+    /// 设置辐亮度的整体缩放，默认为 0.03，此时高空的阳光照在白色漫反射表面上的亮度约为 1。
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// 设置太阳圆盘的角半径，单位为角度，默认为 0.2667°。
+    ///
+    /// 太阳的总辐照度保持不变，圆盘越大，阴影的边缘越柔和。
+    pub fn sun_angular_radius(mut self, degrees: f64) -> Self {
+        self.sun_cos_radius = degrees.to_radians().cos();
+        self
+    }
+
+    /// 设置场景包围球，默认以原点为球心、半径为 1000，包围球越贴合场景，发出的阳光越不容易错过场景。
+    pub fn scene_bounds(mut self, center: Point3, radius: f64) -> Self {
+        self.scene_center = center;
+        self.scene_radius = radius;
+        self
+    }
+
+    /// 太阳圆盘所占的立体角
+    fn sun_solid_angle(&self) -> f64 {
+        2.0 * PI * (1.0 - self.sun_cos_radius)
+    }
+
+    /// 太阳圆盘上的辐亮度，未经缩放
+    fn sun_radiance(&self) -> Color {
+        if self.sun_direction.y <= 0.0 {
+            return Color::zero();
+        }
+        self.sun_irradiance / self.sun_solid_angle()
+    }
+
+    /// 在太阳圆盘内均匀采样方向的立体角概率密度
+    fn sun_pdf(&self) -> f64 {
+        1.0 / self.sun_solid_angle()
+    }
+
+    /// 在太阳圆盘所在的圆锥内均匀采样一个指向太阳的方向
+    fn sample_sun_direction(&self) -> Vec3 {
+        let cos_theta = 1.0 - random_double() * (1.0 - self.sun_cos_radius);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random_double();
+        let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        Onb::new(self.sun_direction).transform(local).unit_vector()
+    }
+
+    /// 逆着 `w` 方向发射光线的圆盘圆心，位于包围球外朝向 `w` 的一侧
+    fn disk_center(&self, w: Vec3) -> Point3 {
+        self.scene_center + self.scene_radius * w
+    }
+
+    /// 发射圆盘的面积概率密度
+    fn disk_pdf(&self) -> f64 {
+        1.0 / (PI * self.scene_radius * self.scene_radius)
+    }
+
+    /// 阳光穿过大气层后按瑞利散射和气溶胶散射衰减的透射率，RGB 分量分别取一个代表波长计算。
+    fn sun_transmittance(sun_theta: f64, turbidity: f64) -> Color {
+        // 相对光学质量，即阳光穿过的大气厚度与天顶方向厚度之比
+        let mass = 1.0 / (sun_theta.cos() + 0.15 * (93.885 - sun_theta.to_degrees()).powf(-1.253));
+        // Ångström 浑浊度公式
+        let beta = 0.04608 * turbidity - 0.04586;
+        let alpha = 1.3;
+
+        // 波长单位为微米
+        let transmittance = |lambda: f64| {
+            let rayleigh = (-0.008735 * lambda.powf(-4.08) * mass).exp();
+            let aerosol = (-beta * lambda.powf(-alpha) * mass).exp();
+            rayleigh * aerosol
+        };
+        Color::new(
+            transmittance(0.65),
+            transmittance(0.57),
+            transmittance(0.475),
+        )
+    }
+
+    /// 天顶处的亮度 Y（千坎德拉每平方米）以及色度 x、y。
+    fn zenith(sun_theta: f64, turbidity: f64) -> [f64; 3] {
+        let t = turbidity;
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * sun_theta);
+        let luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+
+        let theta = [sun_theta.powi(3), sun_theta.powi(2), sun_theta, 1.0];
+        let chromaticity = |m: [[f64; 4]; 3]| {
+            let row = |r: [f64; 4]| r.iter().zip(theta).map(|(a, b)| a * b).sum::<f64>();
+            t * t * row(m[0]) + t * row(m[1]) + row(m[2])
+        };
+        let x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+        [luminance.max(0.0), x, y]
+    }
+
+    /// 地平线以上单位方向向量 `w` 的天空辐亮度，未经缩放，不包括太阳圆盘。
+    fn sky_radiance(&self, w: Vec3) -> Color {
+        let cos_theta = w.y.max(0.0);
+        let gamma = w.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let [luminance, x, y] = std::array::from_fn(|i| {
+            let perez = self.perez[i];
+            self.zenith[i] * perez.eval(cos_theta, gamma) / perez.eval(1.0, self.sun_theta)
+        });
+        if luminance <= 0.0 || y <= 0.0 {
+            return Color::zero();
+        }
+
+        // xyY 转换到 XYZ 再转换到线性 sRGB
+        let xyz = Color::new(x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+        let rgb = xyz_to_linear_srgb(xyz);
+        Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+    }
+
+    /// 对天空辐亮度在上半球内做数值积分，得到水平地面接收到的辐照度。
+    fn integrate_sky_irradiance(&self) -> Color {
+        let (theta_steps, phi_steps) = IRRADIANCE_STEPS;
+        let d_theta = FRAC_PI_2 / theta_steps as f64;
+        let d_phi = 2.0 * PI / phi_steps as f64;
+
+        let mut irradiance = Color::zero();
+        for i in 0..theta_steps {
+            let theta = (i as f64 + 0.5) * d_theta;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            for j in 0..phi_steps {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let w = Vec3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin());
+                irradiance += self.sky_radiance(w) * (cos_theta * sin_theta * d_theta * d_phi);
+            }
+        }
+        irradiance
+    }
+
+    /// 沿单位方向向量 `w` 看到的辐亮度，已经过缩放。
+    fn radiance(&self, w: Vec3) -> Color {
+        if w.y < 0.0 {
+            // 漫反射地面：L = ρ * E / π
+            let sun = self.sun_irradiance * self.sun_direction.y.max(0.0);
+            return self.scale * self.ground_albedo * (sun + self.sky_irradiance) / PI;
+        }
+
+        let mut radiance = self.sky_radiance(w);
+        if w.dot(self.sun_direction) >= self.sun_cos_radius {
+            radiance += self.sun_radiance();
+        }
+        self.scale * radiance
+    }
+}
+
+impl Light for SkyLight {
+    fn sample(&self, _: Point3) -> Option<LightSample> {
+        if self.sun_radiance().near_zero() {
+            return None;
+        }
+
+        let direction = self.sample_sun_direction();
+
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            radiance: self.radiance(direction),
+            pdf: self.sun_pdf(),
+            normal: None,
+            is_delta: false,
+        })
+    }
+
+    fn pdf(&self, _: Point3, direction: Vec3) -> f64 {
+        if self.sun_radiance().near_zero()
+            || direction.unit_vector().dot(self.sun_direction) < self.sun_cos_radius
+        {
+            return 0.0;
+        }
+        self.sun_pdf()
+    }
+
+    fn sample_emission(&self) -> Option<EmissionSample> {
+        if self.scene_radius <= 0.0 || self.sun_radiance().near_zero() {
+            return None;
+        }
+
+        // 先采样指向太阳的方向，再在垂直于该方向的圆盘上均匀采样发射点
+        let w = self.sample_sun_direction();
+        let disk = Vec3::random_in_unit_disk();
+        let onb = Onb::new(w);
+        let origin =
+            self.disk_center(w) + self.scene_radius * (disk.x * onb.u() + disk.y * onb.v());
+
+        Some(EmissionSample {
+            ray: Ray::new(origin, -w),
+            normal: None,
+            radiance: self.radiance(w),
+            pdf_pos: self.disk_pdf(),
+            pdf_dir: self.sun_pdf(),
+        })
+    }
+
+    fn pdf_emission(&self, p: Point3, direction: Vec3) -> (f64, f64) {
+        let w = -direction.unit_vector();
+        if self.scene_radius <= 0.0 || self.pdf(p, w) <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        // 检查 p 是否在逆着 direction 发射光线的圆盘上
+        let offset = p - self.disk_center(w);
+        let height = offset.dot(w);
+        let radial = offset - height * w;
+        let tolerance = 1e-6 * self.scene_radius;
+        if height.abs() > tolerance || radial.length() > self.scene_radius + tolerance {
+            return (0.0, 0.0);
+        }
+        (self.disk_pdf(), self.sun_pdf())
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
+    fn background(&self, r: Ray) -> Color {
+        self.radiance(r.direction.unit_vector())
+    }
+}
+
+/// Perez 天空亮度分布函数的五个系数
+#[derive(Copy, Clone)]
+struct Perez([f64; 5]);
+
+impl Perez {
+    /// Preetham 模型中由浑浊度拟合得到的亮度 Y 以及色度 x、y 的系数。
+    fn coefficients(turbidity: f64) -> [Self; 3] {
+        let t = turbidity;
+        [
+            Self([
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ]),
+            Self([
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ]),
+            Self([
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ]),
+        ]
+    }
+
+    /// 计算天顶角余弦为 `cos_theta`、与太阳夹角为 `gamma` 的方向上的相对分布。
+    fn eval(self, cos_theta: f64, gamma: f64) -> f64 {
+        let [a, b, c, d, e] = self.0;
+        let cos_gamma = gamma.cos();
+        (1.0 + a * (b / cos_theta.max(0.01)).exp())
+            * (1.0 + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+    }
+}
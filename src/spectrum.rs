@@ -0,0 +1,394 @@
+//! 光谱渲染使用的波长采样、光谱表示，以及 RGB、CIE XYZ 与 sRGB 之间的转换。
+
+use crate::color::Color;
+use crate::utils::random_double;
+use std::ops::{AddAssign, Div, Mul, MulAssign};
+
+/// 每条相机射线同时追踪的波长数量
+pub const SPECTRUM_SAMPLES: usize = 4;
+
+/// 采样的可见光波长下限，单位为纳米
+pub const LAMBDA_MIN: f64 = 380.0;
+
+/// 采样的可见光波长上限，单位为纳米
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// 一组采样得到的波长以及各自的概率密度
+///
+/// 使用主波长（hero wavelength）采样：随机选择一个主波长，其余波长在可见光范围内等间距地
+/// 循环排列。主波长决定色散等与波长相关的散射方向，其余波长只在散射与波长无关时一起追踪。
+#[derive(Debug, Copy, Clone)]
+pub struct SampledWavelengths {
+    lambda: [f64; SPECTRUM_SAMPLES],
+    pdf: [f64; SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    /// 在可见光范围内随机采样一组波长。
+    pub fn sample() -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = random_double() * range;
+        let delta = range / SPECTRUM_SAMPLES as f64;
+
+        let mut lambda = [0.0; SPECTRUM_SAMPLES];
+        for (i, lambda) in lambda.iter_mut().enumerate() {
+            *lambda = LAMBDA_MIN + (hero + i as f64 * delta) % range;
+        }
+        Self {
+            lambda,
+            pdf: [1.0 / range; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// 主波长，单位为纳米
+    #[inline]
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    /// 第 `i` 个波长，单位为纳米
+    #[inline]
+    pub fn lambda(&self, i: usize) -> f64 {
+        self.lambda[i]
+    }
+
+    /// 第 `i` 个波长的概率密度，被终止的波长为 0
+    #[inline]
+    pub fn pdf(&self, i: usize) -> f64 {
+        self.pdf[i]
+    }
+
+    /// 是否只剩下主波长
+    #[inline]
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+
+    /// 终止除主波长以外的所有波长。
+    ///
+    /// 散射方向与波长相关（例如色散）时，其余波长无法沿主波长的路径继续传播。
+    /// 主波长的概率密度同时除以波长数量，使估计保持无偏。
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        for pdf in &mut self.pdf[1..] {
+            *pdf = 0.0;
+        }
+        self.pdf[0] /= SPECTRUM_SAMPLES as f64;
+    }
+}
+
+/// 在一组采样波长上取值的光谱
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SampledSpectrum {
+    pub values: [f64; SPECTRUM_SAMPLES],
+}
+
+impl SampledSpectrum {
+    /// 创建一个所有波长取值都相同的光谱。
+    #[inline]
+    pub fn constant(value: f64) -> Self {
+        Self {
+            values: [value; SPECTRUM_SAMPLES],
+        }
+    }
+
+    /// 将线性 sRGB 颜色上采样为光谱，并在给定的波长上取值。
+    ///
+    /// 使用 Smits 的方法：把颜色分解为白、青、品红、黄、红、绿、蓝几种基础光谱的非负组合。
+    /// 对反射率和发光强度都适用，颜色各分量在 [0, 1] 内时得到的光谱也基本在 [0, 1] 内。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::Color;
+    /// use ray_tracing_in_one_weekend::spectrum::{SampledSpectrum, SampledWavelengths};
+    ///
+    /// // 纯红色上采样为光谱后再转换回 RGB，大致得到原来的颜色
+    /// let red = Color::new(1.0, 0.0, 0.0);
+    /// let n = 20000;
+    /// let mut sum = Color::zero();
+    /// for _ in 0..n {
+    ///     let wavelengths = SampledWavelengths::sample();
+    ///     sum += SampledSpectrum::from_rgb(red, &wavelengths).to_rgb(&wavelengths);
+    /// }
+    /// let rgb = sum / n as f64;
+    /// assert!((rgb - red).length() < 0.05, "{rgb:?}");
+    /// ```
+    pub fn from_rgb(color: Color, wavelengths: &SampledWavelengths) -> Self {
+        let mut values = [0.0; SPECTRUM_SAMPLES];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = rgb_to_spectrum(color, wavelengths.lambda(i));
+        }
+        Self { values }
+    }
+
+    /// 所有波长中的最大取值
+    #[inline]
+    pub fn max_value(&self) -> f64 {
+        self.values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// 是否所有波长上的取值都为 0
+    #[inline]
+    pub fn is_black(&self) -> bool {
+        self.values.iter().all(|&value| value == 0.0)
+    }
+
+    /// 转换为 CIE XYZ 颜色，结果使用 Vec3 的 x、y、z 分量保存。
+    ///
+    /// 这是对光谱与颜色匹配函数乘积积分的蒙特卡洛估计，并按照 Y 的积分进行了归一化，
+    /// 所以取值恒为 1 的光谱的亮度 Y 约为 1。
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> Color {
+        let mut xyz = Color::zero();
+        for i in 0..SPECTRUM_SAMPLES {
+            let pdf = wavelengths.pdf(i);
+            if pdf == 0.0 {
+                continue;
+            }
+            let lambda = wavelengths.lambda(i);
+            xyz += Color::new(cie_x(lambda), cie_y(lambda), cie_z(lambda)) * self.values[i] / pdf;
+        }
+        xyz / (SPECTRUM_SAMPLES as f64 * CIE_Y_INTEGRAL)
+    }
+
+    /// 转换为线性 sRGB 颜色。
+    ///
+    /// 结果按照等能白光进行了白平衡，即取值恒为 1 的光谱会得到白色 (1, 1, 1)。
+    ///
+    /// # 示例
+    /// ```
+    /// use ray_tracing_in_one_weekend::Color;
+    /// use ray_tracing_in_one_weekend::spectrum::{SampledSpectrum, SampledWavelengths};
+    ///
+    /// // 对随机采样的波长取平均，估计值收敛到白色
+    /// let n = 20000;
+    /// let mut sum = Color::zero();
+    /// for _ in 0..n {
+    ///     let wavelengths = SampledWavelengths::sample();
+    ///     sum += SampledSpectrum::constant(1.0).to_rgb(&wavelengths);
+    /// }
+    /// let rgb = sum / n as f64;
+    /// assert!((rgb - Color::one()).length() < 0.02, "{rgb:?}");
+    /// ```
+    pub fn to_rgb(&self, wavelengths: &SampledWavelengths) -> Color {
+        let rgb = xyz_to_linear_srgb(self.to_xyz(wavelengths));
+        Color::new(
+            rgb.x / EQUAL_ENERGY_WHITE.x,
+            rgb.y / EQUAL_ENERGY_WHITE.y,
+            rgb.z / EQUAL_ENERGY_WHITE.z,
+        )
+    }
+}
+
+/// 实现两个光谱的乘法运算，即各个波长上的取值按位相乘。
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    #[inline]
+    fn mul(mut self, rhs: Self) -> Self::Output {
+        for (value, rhs) in self.values.iter_mut().zip(rhs.values) {
+            *value *= rhs;
+        }
+        self
+    }
+}
+
+/// 实现光谱乘以一个标量。
+impl Mul<f64> for SampledSpectrum {
+    type Output = Self;
+
+    #[inline]
+    fn mul(mut self, rhs: f64) -> Self::Output {
+        for value in &mut self.values {
+            *value *= rhs;
+        }
+        self
+    }
+}
+
+/// 实现光谱除以一个标量。
+impl Div<f64> for SampledSpectrum {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: f64) -> Self::Output {
+        self * (1.0 / rhs)
+    }
+}
+
+/// 实现两个光谱的加法运算，并赋值给当前光谱（+=）。
+impl AddAssign for SampledSpectrum {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        for (value, rhs) in self.values.iter_mut().zip(rhs.values) {
+            *value += rhs;
+        }
+    }
+}
+
+/// 实现两个光谱的乘法运算，并赋值给当前光谱（*=）。
+impl MulAssign for SampledSpectrum {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// 实现光谱乘以一个标量，并赋值给当前光谱（*=）。
+impl MulAssign<f64> for SampledSpectrum {
+    #[inline]
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
+    }
+}
+
+/// Smits 基础光谱的起始波长，单位为纳米
+const SMITS_LAMBDA_MIN: f64 = 380.0;
+
+/// Smits 基础光谱的结束波长，单位为纳米
+const SMITS_LAMBDA_MAX: f64 = 720.0;
+
+/// Smits 基础光谱在 [380, 720] nm 内等宽分段的取值
+const SMITS_WHITE: [f64; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// 计算线性 sRGB 颜色上采样得到的光谱在波长 `lambda` 处的取值。
+fn rgb_to_spectrum(color: Color, lambda: f64) -> f64 {
+    // 超出基础光谱范围的波长使用两端分段的取值
+    let t = (lambda - SMITS_LAMBDA_MIN) / (SMITS_LAMBDA_MAX - SMITS_LAMBDA_MIN);
+    let bin = ((t * 10.0).floor().max(0.0) as usize).min(9);
+    let (r, g, b) = (color.x, color.y, color.z);
+
+    // 先取三个分量中的最小值作为白色部分，剩下的部分用两种基础光谱补足
+    if r <= g && r <= b {
+        let rest = if g <= b {
+            (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        };
+        r * SMITS_WHITE[bin] + rest
+    } else if g <= r && g <= b {
+        let rest = if r <= b {
+            (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        };
+        g * SMITS_WHITE[bin] + rest
+    } else {
+        let rest = if r <= g {
+            (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        };
+        b * SMITS_WHITE[bin] + rest
+    }
+}
+
+/// CIE 1931 颜色匹配函数 ȳ 在 [380, 780] nm 上的积分
+const CIE_Y_INTEGRAL: f64 = 106.919_734_6;
+
+/// 等能白光（取值恒为 1 的光谱）归一化后转换得到的线性 sRGB 颜色
+const EQUAL_ENERGY_WHITE: Color = Color {
+    x: 1.200_551_9,
+    y: 0.949_765_1,
+    z: 0.907_686_1,
+};
+
+/// 分段高斯函数，波长小于 `mu` 和大于 `mu` 时分别使用不同的标准差。
+#[inline]
+fn piecewise_gaussian(lambda: f64, mu: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if lambda < mu { sigma_low } else { sigma_high };
+    let t = (lambda - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 颜色匹配函数 x̄ 的多峰高斯拟合（Wyman 等，2013）。
+pub fn cie_x(lambda: f64) -> f64 {
+    1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2)
+}
+
+/// CIE 1931 颜色匹配函数 ȳ 的多峰高斯拟合（Wyman 等，2013）。
+pub fn cie_y(lambda: f64) -> f64 {
+    0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1)
+}
+
+/// CIE 1931 颜色匹配函数 z̄ 的多峰高斯拟合（Wyman 等，2013）。
+pub fn cie_z(lambda: f64) -> f64 {
+    1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8)
+}
+
+/// 将 CIE XYZ 颜色转换为线性 sRGB 颜色（D65 白点）。
+pub fn xyz_to_linear_srgb(xyz: Color) -> Color {
+    Color::new(
+        3.2406 * xyz.x - 1.5372 * xyz.y - 0.4986 * xyz.z,
+        -0.9689 * xyz.x + 1.8758 * xyz.y + 0.0415 * xyz.z,
+        0.0557 * xyz.x - 0.2040 * xyz.y + 1.0570 * xyz.z,
+    )
+}
+
+/// 电介质折射率随波长变化的色散模型，公式中的波长单位均为微米。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Dispersion {
+    /// Cauchy 公式：`n(λ) = a + b / λ²`
+    Cauchy { a: f64, b: f64 },
+    /// Sellmeier 公式：`n²(λ) = 1 + Σ bᵢ λ² / (λ² - cᵢ)`
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// 计算波长为 `lambda`（单位为纳米）的光的折射率。
+    pub fn refraction_index(&self, lambda: f64) -> f64 {
+        let micrometers = lambda * 1e-3;
+        let l2 = micrometers * micrometers;
+        match self {
+            Self::Cauchy { a, b } => a + b / l2,
+            Self::Sellmeier { b, c } => {
+                let n2 = 1.0 + b.iter().zip(c).map(|(b, c)| b * l2 / (l2 - c)).sum::<f64>();
+                n2.max(1.0).sqrt()
+            }
+        }
+    }
+
+    /// 冕牌玻璃 BK7 的 Sellmeier 系数
+    pub fn bk7() -> Self {
+        Self::Sellmeier {
+            b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+            c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+        }
+    }
+
+    /// 钻石的 Sellmeier 系数
+    pub fn diamond() -> Self {
+        Self::Sellmeier {
+            b: [4.3356, 0.3306, 0.0],
+            c: [0.106 * 0.106, 0.175 * 0.175, 0.0],
+        }
+    }
+}
@@ -1,10 +1,14 @@
 //! 球体类型的定义，以及它的光线碰撞检测逻辑。
 
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::{Point3, Ray};
+use crate::utils::random_double;
 use crate::vec3::Vec3;
+use std::f64::consts::PI;
 use std::rc::Rc;
 
 /// 球体类型定义，包含球心和半径。
@@ -25,6 +29,48 @@ impl Sphere {
             mat,
         }
     }
+
+    /// 计算球面上一点的表面坐标以及对应的切线。
+    ///
+    /// u 为绕 y 轴的角度（从 -x 方向开始），v 为从 -y 方向开始的极角，两者都归一化到 [0, 1]。
+    ///
+    /// # 参数
+    ///
+    /// * `outward_normal` - 该点朝外的单位法线
+    ///
+    /// # 返回值
+    ///
+    /// `(u, v, ∂p/∂u, ∂p/∂v)`
+    fn surface_coordinates(&self, outward_normal: Vec3) -> (f64, f64, Vec3, Vec3) {
+        let Vec3 { x, y, z } = outward_normal;
+        let theta = (-y).clamp(-1.0, 1.0).acos();
+        let phi = (-z).atan2(x) + PI;
+        let u = phi / (2.0 * PI);
+        let v = theta / PI;
+
+        let sin_theta = (x * x + z * z).sqrt().max(1e-12);
+        let dpdu = 2.0 * PI * self.radius * Vec3::new(z, 0.0, -x);
+        let dpdv = PI * self.radius * Vec3::new(-x * y / sin_theta, sin_theta, -y * z / sin_theta);
+        (u, v, dpdu, dpdv)
+    }
+
+    /// 在以 z 轴为中心、恰好覆盖球体的圆锥内均匀采样一个方向。
+    ///
+    /// # 参数
+    ///
+    /// * `radius` - 球体半径
+    /// * `distance_squared` - 采样点到球心距离的平方
+    fn random_to_sphere(radius: f64, distance_squared: f64) -> Vec3 {
+        let r1 = random_double();
+        let r2 = random_double();
+        let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+        let phi = 2.0 * std::f64::consts::PI * r1;
+        let x = phi.cos() * (1.0 - z * z).sqrt();
+        let y = phi.sin() * (1.0 - z * z).sqrt();
+
+        Vec3::new(x, y, z)
+    }
 }
 
 impl Hittable for Sphere {
@@ -51,12 +97,68 @@ impl Hittable for Sphere {
         // 有解，并且在 t_min 和 t_max 之间，计算 HitRecord
         let point = r.at(root);
         let outward_normal = (point - self.center) / self.radius;
-        Some(HitRecord::new(
-            point,
-            outward_normal,
-            root,
-            r,
-            self.mat.as_ref(),
-        ))
+        let (u, v, dpdu, dpdv) = self.surface_coordinates(outward_normal);
+        Some(
+            HitRecord::new(point, outward_normal, root, r, self.mat.as_ref())
+                .with_uv(u, v, dpdu, dpdv)
+                .with_object(self),
+        )
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        // 只有朝向球体的方向才可能被采样到
+        if self
+            .hit(
+                Ray::new(origin, direction),
+                Interval::new(0.001, f64::INFINITY),
+            )
+            .is_none()
+        {
+            return 0.0;
+        }
+
+        // 采样点在球体内部时，无法用圆锥覆盖球体
+        let distance_squared = (self.center - origin).length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return 0.0;
+        }
+
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * std::f64::consts::PI * (1.0 - cos_theta_max);
+
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let direction = self.center - origin;
+        let distance_squared = direction.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return direction;
+        }
+
+        let uvw = Onb::new(direction);
+        uvw.transform(Self::random_to_sphere(self.radius, distance_squared))
+    }
+
+    fn sample_surface(&self) -> Option<HitRecord<'_>> {
+        let outward_normal = Vec3::random_unit();
+        let point = self.center + self.radius * outward_normal;
+        // 构造一条从外部垂直射向采样点的光线，使得碰撞记录的法线朝外
+        let ray = Ray::new(point + outward_normal, -outward_normal);
+        let (u, v, dpdu, dpdv) = self.surface_coordinates(outward_normal);
+        Some(
+            HitRecord::new(point, outward_normal, 1.0, ray, self.mat.as_ref())
+                .with_uv(u, v, dpdu, dpdv)
+                .with_object(self),
+        )
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
     }
 }
@@ -20,6 +20,12 @@ pub fn random_double_range_inclusive(min: f64, max: f64) -> f64 {
     rand::rng().random_range(min..=max)
 }
 
+/// 生成一个 [min,max] 之间的随机整数。
+#[inline]
+pub fn random_int(min: i32, max: i32) -> i32 {
+    rand::rng().random_range(min..=max)
+}
+
 /// 将线性空间的分量转换为 gamma 空间的分量。
 #[inline]
 pub fn linear_to_gamma(linear_component: f64) -> f64 {
@@ -11,7 +11,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssi
 ///
 /// # 示例
 /// ```
-/// use vec3::Vec3;
+/// use ray_tracing_in_one_weekend::vec3::Vec3;
 ///
 /// let vec = Vec3::new(1.0, 2.0, 3.0);
 /// assert_eq!(vec.x, 1.0);
@@ -85,7 +85,7 @@ impl Vec3 {
         loop {
             let random_vector = Self::random_range(-1.0, 1.0);
             let length_squared = random_vector.length_squared();
-            if length_squared >= 1e-160 && length_squared <= 1.0 {
+            if (1e-160..=1.0).contains(&length_squared) {
                 break random_vector / length_squared.sqrt();
             }
         }